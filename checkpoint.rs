@@ -0,0 +1,185 @@
+use crate::model::{read_file, write_file, ElectrodeRecord, ModelFormat};
+use crate::neural::{ModulatedParameter, NeuriteExt, NeuriteID, ShortTermPlasticity, SpikeDelay, SpikeTimingDependentPlasticity, SynapticID};
+use crate::system::SimRng;
+
+/// The current checkpoint file format version. Files with a newer version cannot be loaded.
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+/// Contains data for the dynamic state of a neurite. Parameters are included because modulatory synapses change them.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct NeuriteState {
+    /// The neurite ID.
+    pub id: NeuriteID,
+    /// Timescale for recovery variable u.
+    pub a: f64,
+    /// Sensitivity of recovery variable u to sub-threshold oscillations.
+    pub b: f64,
+    /// After-spike reset value of the membrane potential v.
+    pub c: f64,
+    /// After-spike reset value of recovery variable u.
+    pub d: f64,
+    /// Membrane potential recovery variable.
+    pub u: f64,
+    /// Membrane potential/voltage.
+    pub v: f64,
+    /// Total coupling input from child compartment(s).
+    pub vcc: f64,
+    /// Coupling input from parent compartment.
+    pub vpc: f64,
+    /// Spike output.
+    pub y: f64,
+    /// Spike time offset within the last time step.
+    pub ts: f64,
+    /// Extended variables (optional).
+    pub ext: Option<NeuriteExt>,
+    /// Parameters modulated by neural modulatory synapses, with their values before modulation.
+    pub modulated: Vec<ModulatedParameter>
+}
+
+/// Contains a list of long-term plasticity states.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum LtpState {
+    /// STDP data, including its spike traces.
+    Stdp(SpikeTimingDependentPlasticity),
+    /// Reward-modulated STDP data and the eligibility trace.
+    RewardModulated(SpikeTimingDependentPlasticity, f64)
+}
+
+/// Contains data for the dynamic state of a synapse. Fields a synapse type does not have are 0 (or none).
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SynapseState {
+    /// The synaptic ID.
+    pub id: SynapticID,
+    /// The input conductance of the synapse.
+    pub x: f64,
+    /// The conductance of a gap junction from input a.
+    pub ax: f64,
+    /// The conductance of a gap junction from input b.
+    pub bx: f64,
+    /// Synaptic weight.
+    pub w: f64,
+    /// Short-term plasticity data (none for a gap junction).
+    pub stp: Option<ShortTermPlasticity>,
+    /// Conduction delay, including spikes in transit (optional).
+    pub delay: Option<SpikeDelay>,
+    /// Long-term plasticity state (optional).
+    pub ltp: Option<LtpState>
+}
+
+// SynapseState functions
+impl SynapseState {
+    /// Creates a new synapse state with the specified synaptic ID, and every other field 0 (or none).
+    pub fn new(id: SynapticID) -> Self {
+        Self {
+            id,
+            x: 0.0,
+            ax: 0.0,
+            bx: 0.0,
+            w: 0.0,
+            stp: None,
+            delay: None,
+            ltp: None
+        }
+    }
+}
+
+/// Contains a list of electrode states.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum ElectrodeState {
+    /// The complete electrode, including its random number generator.
+    Record(Box<ElectrodeRecord>),
+    /// Voltage clamp active flag, time active, output and recorded clamp currents.
+    VoltageClamp(bool, f64, f64, Vec<(f64, f64)>),
+    /// Conductance electrode active flag and output.
+    Conductance(bool, f64)
+}
+
+/// Contains data for the dynamic state of a sensor. Fields a sensor type does not have are 0 (or none).
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SensorState {
+    /// The length of time the sensor has been processed (or the time into the current window of an encoder).
+    pub a: f64,
+    /// Current output of the sensor.
+    pub y: f64,
+    /// The reference value of a delta encoder, or the latency within the current window of a latency encoder.
+    pub x: Option<f64>,
+    /// Random number generator.
+    pub rng: Option<SimRng>
+}
+
+// SensorState functions
+impl SensorState {
+    /// Creates a new sensor state with the specified output, and every other field 0 (or none).
+    pub fn new(y: f64) -> Self {
+        Self {
+            a: 0.0,
+            y,
+            x: None,
+            rng: None
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a checkpoint of the dynamic state of a simulation at a time step. A checkpoint is restored onto a
+/// simulation of the same model (such as one loaded from the same model file); neurites and synapses are matched by ID
+/// and electrodes, sensors, dopamine signals and protocols by order.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Checkpoint {
+    /// The checkpoint file format version.
+    pub(crate) version: u32,
+    /// The simulation time step (ms).
+    pub(crate) time_inc: f64,
+    /// The number of time steps processed.
+    pub(crate) steps: u64,
+    /// The neurite states.
+    pub(crate) neurites: Vec<NeuriteState>,
+    /// The synapse states.
+    pub(crate) synapses: Vec<SynapseState>,
+    /// The electrode states.
+    pub(crate) electrodes: Vec<ElectrodeState>,
+    /// The sensor states.
+    pub(crate) sensors: Vec<SensorState>,
+    /// The dopamine concentration (excluding the tonic concentration) of each dopamine signal.
+    pub(crate) dopamine: Vec<f64>,
+    /// The position (next command and time steps processed) of each protocol.
+    pub(crate) protocols: Vec<(usize, u64)>
+}
+
+// Checkpoint functions
+impl Checkpoint {
+    /// Returns the number of time steps processed at the checkpoint.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Saves the checkpoint to the specified path in the binary or RON format, which restore bit-exactly. JSON is
+    /// rejected, as it does not read back every value exactly.
+    pub fn save(&self, path: &str, format: ModelFormat) -> Result<(), String> {
+        check_format(format)?;
+        write_file(path, self, format, "checkpoint")
+    }
+
+    /// Loads a checkpoint from the specified path in the specified format. Files with a newer format version than this
+    /// program supports are rejected.
+    pub fn load(path: &str, format: ModelFormat) -> Result<Self, String> {
+        check_format(format)?;
+        let checkpoint: Self = read_file(path, format, "checkpoint")?;
+
+        if checkpoint.version > CHECKPOINT_VERSION {
+            return Err(format!("Checkpoint file {} has version {}, but only versions up to {} are supported.", path,
+                               checkpoint.version, CHECKPOINT_VERSION));
+        }
+
+        Ok(checkpoint)
+    }
+}
+
+/// Returns an error message if the specified format cannot hold a checkpoint.
+fn check_format(format: ModelFormat) -> Result<(), String> {
+    match format {
+        ModelFormat::Json => Err("Checkpoints cannot be saved as JSON.".to_string()),
+        _ => Ok(())
+    }
+}
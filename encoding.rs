@@ -0,0 +1,343 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use rand::{Rng, SeedableRng};
+use crate::checkpoint::SensorState;
+use crate::neural::Sensor;
+use crate::system::SimRng;
+
+// Encoders are sensors that read the output of an analog source sensor and output 1 on the time steps they spike and 0
+// otherwise, so they can be used as a presynaptic input. Encoders do not process their source; the source must be
+// processed first (added to a simulation before the encoder).
+
+/// Contains data for a rate (poisson) encoder.
+pub struct RateEncoder {
+    /// The analog source.
+    source: Rc<RefCell<dyn Sensor>>,
+    /// The spike rate per unit of source output (Hz).
+    gain: f64,
+    /// The maximum spike rate (Hz).
+    max: f64,
+    /// Current output of the encoder.
+    y: f64,
+    /// Random number generator.
+    rng: SimRng
+}
+
+// Sensor functions
+impl Sensor for RateEncoder {
+    /// Returns the current output of the encoder.
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    /// Processes and returns the next output of the encoder after the specified time step (ms). The encoder spikes with
+    /// a probability of rate * time step.
+    fn process(&mut self, time: f64) -> f64 {
+        let rate = (self.gain * self.source.borrow().output()).max(0.0).min(self.max);
+
+        self.y = if self.rng.gen::<f64>() < rate * time / 1000.0 { 1.0 } else { 0.0 };
+        self.y
+    }
+
+    /// Seeds the encoder's random number generator.
+    fn seed(&mut self, rng: SimRng) {
+        self.rng = rng;
+    }
+
+    /// Returns the dynamic state of the encoder, including its random number generator.
+    fn state(&self) -> SensorState {
+        let mut state = SensorState::new(self.y);
+        state.rng = Some(self.rng.clone());
+
+        state
+    }
+
+    /// Restores the dynamic state of the encoder.
+    fn restore(&mut self, state: &SensorState) {
+        self.y = state.y;
+
+        if let Some(rng) = &state.rng {
+            self.rng = rng.clone();
+        }
+    }
+}
+
+// RateEncoder functions
+impl RateEncoder {
+    /// Creates a new rate encoder with the specified source, gain (Hz per unit) and maximum rate (Hz).
+    pub fn new(source: Rc<RefCell<dyn Sensor>>, gain: f64, max: f64) -> Self {
+        Self {
+            source,
+            gain,
+            max,
+            y: 0.0,
+            rng: SimRng::from_entropy()
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a time-to-first-spike (latency) encoder.
+pub struct LatencyEncoder {
+    /// The analog source.
+    source: Rc<RefCell<dyn Sensor>>,
+    /// The length of each encoding window (ms).
+    window: f64,
+    /// The source output that spikes at the start of a window.
+    x_max: f64,
+    /// The length of time since the start of the current window.
+    a: f64,
+    /// The spike latency in the current window (none if the encoder does not spike).
+    latency: Option<f64>,
+    /// Current output of the encoder.
+    y: f64
+}
+
+// Sensor functions
+impl Sensor for LatencyEncoder {
+    /// Returns the current output of the encoder.
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    /// Processes and returns the next output of the encoder after the specified time step (ms). The source is read at
+    /// the start of each window and the encoder spikes once, earlier for larger values. Values at or below 0 do not
+    /// spike.
+    fn process(&mut self, time: f64) -> f64 {
+        if self.a >= self.window {
+            self.a -= self.window;
+        }
+
+        if self.a < time {
+            let x = self.source.borrow().output();
+
+            self.latency = if x > 0.0 {
+                Some(self.window * (1.0 - (x / self.x_max).min(1.0)))
+            }
+            else {
+                None
+            };
+        }
+
+        self.y = match self.latency {
+            Some(l) if l >= self.a && l < self.a + time => 1.0,
+            _ => 0.0
+        };
+
+        self.a += time;
+        self.y
+    }
+
+    /// Returns the dynamic state of the encoder.
+    fn state(&self) -> SensorState {
+        let mut state = SensorState::new(self.y);
+        state.a = self.a;
+        state.x = self.latency;
+
+        state
+    }
+
+    /// Restores the dynamic state of the encoder.
+    fn restore(&mut self, state: &SensorState) {
+        self.a = state.a;
+        self.latency = state.x;
+        self.y = state.y;
+    }
+}
+
+// LatencyEncoder functions
+impl LatencyEncoder {
+    /// Creates a new latency encoder with the specified source, window length (ms) and source output that spikes at
+    /// the start of a window.
+    pub fn new(source: Rc<RefCell<dyn Sensor>>, window: f64, x_max: f64) -> Self {
+        Self {
+            source,
+            window,
+            x_max,
+            a: 0.0,
+            latency: None,
+            y: 0.0
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a population coding encoder with a gaussian tuning curve.
+pub struct TuningCurveEncoder {
+    /// The analog source.
+    source: Rc<RefCell<dyn Sensor>>,
+    /// The preferred source output.
+    preferred: f64,
+    /// The width of the tuning curve.
+    sigma: f64,
+    /// The spike rate at the preferred source output (Hz).
+    max: f64,
+    /// Current output of the encoder.
+    y: f64,
+    /// Random number generator.
+    rng: SimRng
+}
+
+// Sensor functions
+impl Sensor for TuningCurveEncoder {
+    /// Returns the current output of the encoder.
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    /// Processes and returns the next output of the encoder after the specified time step (ms). The encoder spikes as a
+    /// poisson process with a rate given by its tuning curve.
+    fn process(&mut self, time: f64) -> f64 {
+        let d = (self.source.borrow().output() - self.preferred) / self.sigma;
+        let rate = self.max * (-0.5 * d * d).exp();
+
+        self.y = if self.rng.gen::<f64>() < rate * time / 1000.0 { 1.0 } else { 0.0 };
+        self.y
+    }
+
+    /// Seeds the encoder's random number generator.
+    fn seed(&mut self, rng: SimRng) {
+        self.rng = rng;
+    }
+
+    /// Returns the dynamic state of the encoder, including its random number generator.
+    fn state(&self) -> SensorState {
+        let mut state = SensorState::new(self.y);
+        state.rng = Some(self.rng.clone());
+
+        state
+    }
+
+    /// Restores the dynamic state of the encoder.
+    fn restore(&mut self, state: &SensorState) {
+        self.y = state.y;
+
+        if let Some(rng) = &state.rng {
+            self.rng = rng.clone();
+        }
+    }
+}
+
+// TuningCurveEncoder functions
+impl TuningCurveEncoder {
+    /// Creates a new tuning curve encoder with the specified source, preferred source output, tuning curve width and
+    /// maximum rate (Hz).
+    pub fn new(source: Rc<RefCell<dyn Sensor>>, preferred: f64, sigma: f64, max: f64) -> Self {
+        Self {
+            source,
+            preferred,
+            sigma,
+            max,
+            y: 0.0,
+            rng: SimRng::from_entropy()
+        }
+    }
+
+    /// Creates a population of the specified number of tuning curve encoders with preferred source outputs evenly
+    /// spaced over min..max.
+    pub fn population(source: Rc<RefCell<dyn Sensor>>, n: usize, min: f64, max: f64, sigma: f64, max_rate: f64)
+        -> Vec<Rc<RefCell<TuningCurveEncoder>>> {
+        (0..n)
+            .map(|i| {
+                let preferred = if n > 1 { min + (max - min) * i as f64 / (n - 1) as f64 } else { 0.5 * (min + max) };
+                Rc::new(RefCell::new(Self::new(Rc::clone(&source), preferred, sigma, max_rate)))
+            })
+            .collect()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains a list of delta encoder polarities.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Polarity {
+    /// Spikes on increases and decreases.
+    Both,
+    /// Spikes on increases only.
+    On,
+    /// Spikes on decreases only.
+    Off
+}
+
+/// Contains data for a delta (threshold crossing) encoder.
+pub struct DeltaEncoder {
+    /// The analog source.
+    source: Rc<RefCell<dyn Sensor>>,
+    /// The change in source output that causes a spike.
+    th: f64,
+    /// The spike polarity.
+    polarity: Polarity,
+    /// The reference source output (none before the first time step).
+    reference: Option<f64>,
+    /// Current output of the encoder.
+    y: f64
+}
+
+// Sensor functions
+impl Sensor for DeltaEncoder {
+    /// Returns the current output of the encoder.
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    /// Processes and returns the next output of the encoder after the specified time step (ms). The encoder spikes when
+    /// the source output has changed from the reference by the threshold, and the reference moves by the threshold.
+    fn process(&mut self, _time: f64) -> f64 {
+        let x = self.source.borrow().output();
+        let reference = *self.reference.get_or_insert(x);
+
+        self.y = 0.0;
+
+        if x - reference >= self.th {
+            self.reference = Some(reference + self.th * ((x - reference) / self.th).floor());
+
+            if self.polarity != Polarity::Off {
+                self.y = 1.0;
+            }
+        }
+        else if reference - x >= self.th {
+            self.reference = Some(reference - self.th * ((reference - x) / self.th).floor());
+
+            if self.polarity != Polarity::On {
+                self.y = 1.0;
+            }
+        }
+
+        self.y
+    }
+
+    /// Returns the dynamic state of the encoder.
+    fn state(&self) -> SensorState {
+        let mut state = SensorState::new(self.y);
+        state.x = self.reference;
+
+        state
+    }
+
+    /// Restores the dynamic state of the encoder.
+    fn restore(&mut self, state: &SensorState) {
+        self.reference = state.x;
+        self.y = state.y;
+    }
+}
+
+// DeltaEncoder functions
+impl DeltaEncoder {
+    /// Creates a new delta encoder with the specified source, threshold and polarity. If the threshold is not positive
+    /// (and finite), this returns an error message.
+    pub fn new(source: Rc<RefCell<dyn Sensor>>, th: f64, polarity: Polarity) -> Result<Self, &'static str> {
+        if !(th > 0.0 && th.is_finite()) {
+            return Err("Delta encoder threshold must be positive.");
+        }
+
+        Ok(Self {
+            source,
+            th,
+            polarity,
+            reference: None,
+            y: 0.0
+        })
+    }
+}
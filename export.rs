@@ -0,0 +1,374 @@
+use std::fs::{self, File};
+use std::io::Write;
+use crate::neural::{Neurite, NeuriteID};
+use crate::recording::{Probe, ProbeSource, SpikeRecorder};
+
+/// Contains metadata written alongside exported traces and spike trains.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metadata {
+    /// The simulation time step (ms).
+    time_inc: f64,
+    /// The metadata entries as (key, value).
+    entries: Vec<(String, String)>
+}
+
+// Metadata functions
+impl Metadata {
+    /// Creates new metadata with the specified simulation time step (ms) and no entries.
+    pub fn new(time_inc: f64) -> Self {
+        Self {
+            time_inc,
+            entries: vec![]
+        }
+    }
+
+    /// Creates new metadata for the source of the specified probe. A neurite source adds its ID, type, spike model and
+    /// parameter values, and a synapse source adds its ID and type.
+    pub fn from_probe(probe: &Probe, time_inc: f64) -> Self {
+        let mut meta = Self::new(time_inc);
+
+        match probe.source() {
+            ProbeSource::Neurite(n) => {
+                meta.insert("source", "neurite".to_string());
+                meta.insert_neurite("", &n.borrow());
+            }
+            ProbeSource::Synapse(s) => {
+                let s = s.borrow();
+
+                meta.insert("source", "synapse".to_string());
+                meta.insert("synaptic_id", s.syn_id().uuid().to_string());
+                meta.insert("synapse_type", format!("{:?}", s.syn_type()));
+            }
+            ProbeSource::Electrode(_) => meta.insert("source", "electrode".to_string())
+        }
+
+        meta
+    }
+
+    /// Creates new metadata for the neurites of the specified spike recorder. The entries of each neurite are prefixed
+    /// by its ID.
+    pub fn from_spike_recorder(recorder: &SpikeRecorder, time_inc: f64) -> Self {
+        let mut meta = Self::new(time_inc);
+
+        for n in recorder.neurites() {
+            let n = n.borrow();
+            meta.insert_neurite(&format!("{}.", n.id().uuid()), &n);
+        }
+
+        meta
+    }
+
+    /// Adds the ID, type, spike model and parameter values of the specified neurite, with the specified key prefix.
+    fn insert_neurite(&mut self, prefix: &str, n: &Neurite) {
+        self.insert(&format!("{}neurite_id", prefix), n.id().uuid().to_string());
+        self.insert(&format!("{}neurite_type", prefix), format!("{:?}", n.neurite_type()));
+        self.insert(&format!("{}spike_model", prefix), format!("{:?}", n.spike_model()));
+
+        for (name, value) in n.parameters() {
+            self.insert(&format!("{}{}", prefix, name), value.to_string());
+        }
+    }
+
+    /// Adds the specified entry.
+    pub fn insert(&mut self, key: &str, value: String) {
+        self.entries.push((key.to_string(), value));
+    }
+
+    /// Returns the simulation time step (ms).
+    pub fn time_inc(&self) -> f64 {
+        self.time_inc
+    }
+
+    /// Returns the metadata entries as (key, value).
+    pub fn entries(&self) -> &[(String, String)] {
+        &self.entries
+    }
+
+    /// Returns every entry as (key, value), including the time step.
+    fn all_entries(&self) -> Vec<(String, String)> {
+        let mut entries = vec![("time_inc".to_string(), self.time_inc.to_string())];
+        entries.extend(self.entries.iter().cloned());
+        entries
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Writes the specified probe's trace to a CSV file. The metadata is written first as "# key: value" comment lines,
+/// followed by a header row and one row of time and variable values per sample.
+pub fn write_trace_csv(path: &str, probe: &Probe, meta: &Metadata) -> Result<(), String> {
+    let mut text = csv_metadata(meta);
+
+    text.push_str("time");
+
+    for var in probe.vars() {
+        text.push(',');
+        text.push_str(var.name());
+    }
+
+    text.push('\n');
+
+    for (t, sample) in probe.times().iter().zip(probe.samples()) {
+        text.push_str(&t.to_string());
+
+        for value in sample {
+            text.push(',');
+            text.push_str(&value.to_string());
+        }
+
+        text.push('\n');
+    }
+
+    fs::write(path, text).map_err(|e| format!("Cannot write CSV file {}: {}", path, e))
+}
+
+/// Writes the specified spike recorder's spike events to a CSV file. The metadata is written first as "# key: value"
+/// comment lines, followed by a header row and one row of neurite ID and spike time per spike.
+pub fn write_spikes_csv(path: &str, recorder: &SpikeRecorder, meta: &Metadata) -> Result<(), String> {
+    let mut text = csv_metadata(meta);
+
+    text.push_str("neurite_id,time\n");
+
+    for (id, t) in recorder.events() {
+        text.push_str(&format!("{},{}\n", id.uuid(), t));
+    }
+
+    fs::write(path, text).map_err(|e| format!("Cannot write CSV file {}: {}", path, e))
+}
+
+/// Returns an error message if the specified probe records a variable more than once, which would give two arrays or
+/// datasets the same name.
+fn check_trace_names(path: &str, probe: &Probe) -> Result<(), String> {
+    let vars = probe.vars();
+
+    if vars.iter().enumerate().any(|(i, var)| vars[..i].contains(var)) {
+        return Err(format!("Cannot write file {}: the probe records a variable more than once.", path));
+    }
+
+    Ok(())
+}
+
+/// Returns the metadata as "# key: value" comment lines.
+fn csv_metadata(meta: &Metadata) -> String {
+    meta.all_entries().iter().map(|(key, value)| format!("# {}: {}\n", key, value)).collect()
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains a list of array data that can be written in the NumPy format.
+enum NpyData<'a> {
+    F64(&'a [f64]),
+    I64(&'a [i64]),
+    Str(&'a [String])
+}
+
+/// Returns the specified array data with the specified shape in the NumPy (.npy version 1.0) format.
+fn npy_bytes(data: NpyData<'_>, shape: &[usize]) -> Vec<u8> {
+    let width = match &data {
+        NpyData::Str(s) => s.iter().map(|s| s.chars().count()).max().unwrap_or(0).max(1),
+        _ => 0
+    };
+    let descr = match &data {
+        NpyData::F64(_) => "<f8".to_string(),
+        NpyData::I64(_) => "<i8".to_string(),
+        NpyData::Str(_) => format!("<U{}", width)
+    };
+    let shape = match shape {
+        [n] => format!("({},)", n),
+        _ => format!("({})", shape.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(", "))
+    };
+
+    // The header is padded with spaces and ends with a newline so the data starts on a multiple of 64 bytes
+    let mut header = format!("{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}", descr, shape);
+    let len = 10 + header.len() + 1;
+    header.push_str(&" ".repeat((64 - len % 64) % 64));
+    header.push('\n');
+
+    let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+    bytes.extend((header.len() as u16).to_le_bytes());
+    bytes.extend(header.as_bytes());
+
+    match data {
+        NpyData::F64(d) => d.iter().for_each(|x| bytes.extend(x.to_le_bytes())),
+        NpyData::I64(d) => d.iter().for_each(|x| bytes.extend(x.to_le_bytes())),
+        NpyData::Str(d) => {
+            for s in d {
+                let n = s.chars().count();
+
+                s.chars().for_each(|c| bytes.extend((c as u32).to_le_bytes()));
+                bytes.extend(vec![0u8; 4 * (width - n)]);
+            }
+        }
+    }
+
+    bytes
+}
+
+/// Returns the metadata as an array of (key, value) rows in the NumPy format.
+fn npy_metadata(meta: &Metadata) -> Vec<u8> {
+    let entries = meta.all_entries();
+    let flat: Vec<String> = entries.iter().flat_map(|(key, value)| [key.clone(), value.clone()]).collect();
+
+    npy_bytes(NpyData::Str(&flat), &[entries.len(), 2])
+}
+
+/// Writes the specified values with the specified shape (row-major) to a NumPy .npy file.
+pub fn write_npy(path: &str, data: &[f64], shape: &[usize]) -> Result<(), String> {
+    if shape.iter().product::<usize>() != data.len() {
+        return Err(format!("Shape {:?} does not match the {} values.", shape, data.len()));
+    }
+
+    fs::write(path, npy_bytes(NpyData::F64(data), shape)).map_err(|e| format!("Cannot write NPY file {}: {}", path, e))
+}
+
+/// Writes the specified probe's trace to a NumPy .npy file as a 2D array with one row per sample. The first column is
+/// the sample time and the remaining columns are the variables in the order they are recorded. A .npy file cannot hold
+/// metadata; use write_trace_npz to include it.
+pub fn write_trace_npy(path: &str, probe: &Probe) -> Result<(), String> {
+    let data: Vec<f64> = probe.times().iter()
+        .zip(probe.samples())
+        .flat_map(|(&t, sample)| std::iter::once(t).chain(sample.iter().copied()))
+        .collect();
+
+    write_npy(path, &data, &[probe.times().len(), probe.vars().len() + 1])
+}
+
+/// Writes the specified arrays (name and NumPy data) to an uncompressed NumPy .npz archive.
+fn write_npz(path: &str, arrays: Vec<(String, Vec<u8>)>) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Cannot write NPZ file {}: {}", path, e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+    for (name, bytes) in arrays {
+        zip.start_file(format!("{}.npy", name), options)
+            .map_err(|e| format!("Cannot write NPZ file {}: {}", path, e))?;
+        zip.write_all(&bytes).map_err(|e| format!("Cannot write NPZ file {}: {}", path, e))?;
+    }
+
+    zip.finish().map_err(|e| format!("Cannot write NPZ file {}: {}", path, e))?;
+    Ok(())
+}
+
+/// Writes the specified probe's trace to a NumPy .npz archive, with a "time" array, one array per variable (named as
+/// the variable) and a "metadata" array of (key, value) rows.
+pub fn write_trace_npz(path: &str, probe: &Probe, meta: &Metadata) -> Result<(), String> {
+    check_trace_names(path, probe)?;
+
+    let times: Vec<f64> = probe.times().iter().copied().collect();
+    let mut arrays = vec![("time".to_string(), npy_bytes(NpyData::F64(&times), &[times.len()]))];
+
+    for (i, var) in probe.vars().iter().enumerate() {
+        let trace: Vec<f64> = probe.samples().iter().map(|s| s[i]).collect();
+        arrays.push((var.name().to_string(), npy_bytes(NpyData::F64(&trace), &[trace.len()])));
+    }
+
+    arrays.push(("metadata".to_string(), npy_metadata(meta)));
+
+    write_npz(path, arrays)
+}
+
+/// Writes the specified spike recorder's spike events to a NumPy .npz archive as a spike raster: a "neurite_ids" array
+/// of the recorded neurite IDs, "spike_times" and "spike_index" (index into neurite_ids) arrays with one entry per
+/// spike, and a "metadata" array of (key, value) rows.
+pub fn write_spikes_npz(path: &str, recorder: &SpikeRecorder, meta: &Metadata) -> Result<(), String> {
+    let (ids, index, times) = raster(recorder);
+    let ids: Vec<String> = ids.iter().map(|id| id.uuid().to_string()).collect();
+
+    write_npz(path, vec![
+        ("neurite_ids".to_string(), npy_bytes(NpyData::Str(&ids), &[ids.len()])),
+        ("spike_times".to_string(), npy_bytes(NpyData::F64(&times), &[times.len()])),
+        ("spike_index".to_string(), npy_bytes(NpyData::I64(&index), &[index.len()])),
+        ("metadata".to_string(), npy_metadata(meta))
+    ])
+}
+
+/// Returns the recorded neurite IDs and, per spike, the index of the spiking neurite and the spike time (ms).
+fn raster(recorder: &SpikeRecorder) -> (Vec<NeuriteID>, Vec<i64>, Vec<f64>) {
+    let ids: Vec<NeuriteID> = recorder.neurites().iter().map(|n| n.borrow().id()).collect();
+    let index = recorder.events().iter()
+        .map(|(id, _)| ids.iter().position(|i| i == id).map_or(-1, |i| i as i64))
+        .collect();
+    let times = recorder.events().iter().map(|(_, t)| *t).collect();
+
+    (ids, index, times)
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Returns an error message if the specified metadata has a key more than once (including the time step key), which
+/// would give two attributes the same name.
+#[cfg(feature = "hdf5")]
+fn check_metadata_keys(path: &str, meta: &Metadata) -> Result<(), String> {
+    let entries = meta.all_entries();
+
+    if entries.iter().enumerate().any(|(i, (key, _))| entries[..i].iter().any(|(k, _)| k == key)) {
+        return Err(format!("Cannot write file {}: the metadata has a duplicate key.", path));
+    }
+
+    Ok(())
+}
+
+/// Writes the metadata as attributes of the specified HDF5 file. The time step is a float attribute and every other
+/// entry is a string attribute.
+#[cfg(feature = "hdf5")]
+fn hdf5_metadata(file: &hdf5::File, meta: &Metadata) -> hdf5::Result<()> {
+    use hdf5::types::VarLenUnicode;
+
+    file.new_attr::<f64>().create("time_inc")?.write_scalar(&meta.time_inc)?;
+
+    for (key, value) in &meta.entries {
+        let value: VarLenUnicode = value.parse().map_err(|e| hdf5::Error::from(format!("{}", e)))?;
+        file.new_attr::<VarLenUnicode>().create(key.as_str())?.write_scalar(&value)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the specified probe's trace to an HDF5 file, with a "time" dataset, one dataset per variable (named as the
+/// variable) and the metadata as file attributes.
+#[cfg(feature = "hdf5")]
+pub fn write_trace_hdf5(path: &str, probe: &Probe, meta: &Metadata) -> Result<(), String> {
+    check_trace_names(path, probe)?;
+    check_metadata_keys(path, meta)?;
+
+    let write = || -> hdf5::Result<()> {
+        let file = hdf5::File::create(path)?;
+        let times: Vec<f64> = probe.times().iter().copied().collect();
+
+        file.new_dataset_builder().with_data(times.as_slice()).create("time")?;
+
+        for (i, var) in probe.vars().iter().enumerate() {
+            let trace: Vec<f64> = probe.samples().iter().map(|s| s[i]).collect();
+            file.new_dataset_builder().with_data(trace.as_slice()).create(var.name())?;
+        }
+
+        hdf5_metadata(&file, meta)
+    };
+
+    write().map_err(|e| format!("Cannot write HDF5 file {}: {}", path, e))
+}
+
+/// Writes the specified spike recorder's spike events to an HDF5 file as a spike raster, with "neurite_ids",
+/// "spike_times" and "spike_index" datasets (as in write_spikes_npz) and the metadata as file attributes.
+#[cfg(feature = "hdf5")]
+pub fn write_spikes_hdf5(path: &str, recorder: &SpikeRecorder, meta: &Metadata) -> Result<(), String> {
+    use hdf5::types::VarLenUnicode;
+
+    check_metadata_keys(path, meta)?;
+
+    let write = || -> hdf5::Result<()> {
+        let file = hdf5::File::create(path)?;
+        let (ids, index, times) = raster(recorder);
+        let ids: Vec<VarLenUnicode> = ids.iter()
+            .map(|id| id.uuid().to_string().parse().map_err(|e| hdf5::Error::from(format!("{}", e))))
+            .collect::<hdf5::Result<_>>()?;
+
+        file.new_dataset_builder().with_data(ids.as_slice()).create("neurite_ids")?;
+        file.new_dataset_builder().with_data(times.as_slice()).create("spike_times")?;
+        file.new_dataset_builder().with_data(index.as_slice()).create("spike_index")?;
+
+        hdf5_metadata(&file, meta)
+    };
+
+    write().map_err(|e| format!("Cannot write HDF5 file {}: {}", path, e))
+}
@@ -0,0 +1,17 @@
+//! Simulation core of the neuron modeler: neurites, synapses, electrodes, simulations, recording, export and model
+//! files. The GUI program (main.rs) builds on the same modules.
+
+#![warn(clippy::all, rust_2018_idioms)]
+
+pub mod checkpoint;
+pub mod encoding;
+pub mod export;
+pub mod model;
+pub mod neural;
+#[cfg(feature = "hdf5")]
+pub mod nwb;
+pub mod protocol;
+pub mod recording;
+pub mod simulation;
+pub mod swc;
+pub mod system;
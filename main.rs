@@ -2,8 +2,20 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
 mod app;
+mod checkpoint;
 mod neuron_drawing;
 mod draw;
+mod encoding;
+mod export;
+mod model;
+mod neural;
+#[cfg(feature = "hdf5")]
+mod nwb;
+mod protocol;
+mod recording;
+mod simulation;
+mod swc;
+mod system;
 
 use crate::app::NeuronModelerApp;
 
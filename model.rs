@@ -0,0 +1,614 @@
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+use crate::neural::{CommandElectrode, ConductanceElectrode, DopamineSignal, Electrode, ExcitatorySynapse, GapJunctionSynapse,
+                    InhibitorySynapse, Integrator, LongTermPlasticity, Modulation, NeuralModulatorySynapse, Neurite,
+                    NeuriteExt, NeuriteID, NeuriteType, Neuron, OrnsteinUhlenbeckElectrode, PoissonElectrode,
+                    PresynapticInput, PulseElectrode, PulsatingElectrode, RewardModulatedPlasticity, ShortTermPlasticity,
+                    ShotNoiseElectrode, ShuntingInhibitorySynapse, SinusoidalElectrode, SpikeModel,
+                    SpikeTimingDependentPlasticity, Synapse, SynapseType, SynapticID, SynapticModulatorySynapse,
+                    VoltageClampElectrode, WaveformElectrode, WhiteNoiseElectrode};
+
+/// The current model file format version. Files with a newer version cannot be loaded.
+pub const MODEL_VERSION: u32 = 1;
+
+/// Contains a list of model (and checkpoint) file formats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModelFormat {
+    /// JSON. Infinite values (other than modulation bounds) cannot be saved as JSON.
+    Json,
+    Ron,
+    /// Compact binary format (bincode).
+    Binary
+}
+
+/// Writes the specified value to a file at the specified path in the specified format. The kind of file is used in
+/// error messages.
+pub fn write_file<T: serde::Serialize>(path: &str, value: &T, format: ModelFormat, kind: &str) -> Result<(), String> {
+    let bytes = match format {
+        ModelFormat::Json => serde_json::to_string_pretty(value).map(String::into_bytes).map_err(|e| e.to_string()),
+        ModelFormat::Ron => {
+            ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+                .map(String::into_bytes)
+                .map_err(|e| e.to_string())
+        }
+        ModelFormat::Binary => bincode::serialize(value).map_err(|e| e.to_string())
+    }.map_err(|e| format!("Cannot serialize {} file {}: {}", kind, path, e))?;
+
+    fs::write(path, bytes).map_err(|e| format!("Cannot write {} file {}: {}", kind, path, e))
+}
+
+/// Reads a value from the file at the specified path in the specified format. The kind of file is used in error
+/// messages.
+pub fn read_file<T: serde::de::DeserializeOwned>(path: &str, format: ModelFormat, kind: &str) -> Result<T, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Cannot read {} file {}: {}", kind, path, e))?;
+
+    match format {
+        ModelFormat::Json => serde_json::from_slice(&bytes).map_err(|e| e.to_string()),
+        ModelFormat::Ron => {
+            std::str::from_utf8(&bytes)
+                .map_err(|e| e.to_string())
+                .and_then(|text| ron::from_str(text).map_err(|e| e.to_string()))
+        }
+        ModelFormat::Binary => bincode::deserialize(&bytes).map_err(|e| e.to_string())
+    }.map_err(|e| format!("Cannot parse {} file {}: {}", kind, path, e))
+}
+
+/// Serializes f64 values that may be infinite, which JSON cannot hold. Human-readable formats write infinite values as
+/// the strings "inf" and "-inf".
+pub mod extended_f64 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    /// Contains a list of human-readable f64 values.
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        Number(f64),
+        Text(String)
+    }
+
+    /// Serializes the specified value.
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() && !value.is_finite() {
+            serializer.serialize_str(&value.to_string())
+        }
+        else {
+            serializer.serialize_f64(*value)
+        }
+    }
+
+    /// Deserializes a value.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        if !deserializer.is_human_readable() {
+            return f64::deserialize(deserializer);
+        }
+
+        match Value::deserialize(deserializer)? {
+            Value::Number(value) => Ok(value),
+            Value::Text(text) => text.parse().map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+/// Contains a list of presynaptic input references in a model file. Sensors cannot be saved.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum InputRecord {
+    /// Index of an electrode record.
+    Electrode(usize),
+    /// Neurite ID of a neurite record.
+    Neurite(NeuriteID)
+}
+
+/// Contains data for a neurite in a model file.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct NeuriteRecord {
+    /// The neurite ID.
+    pub id: NeuriteID,
+    /// The neurite type.
+    pub neurite_type: NeuriteType,
+    /// The spike model.
+    pub spike_model: SpikeModel,
+    /// Timescale for recovery variable u.
+    pub a: f64,
+    /// Sensitivity of recovery variable u to sub-threshold oscillations.
+    pub b: f64,
+    /// After-spike reset value of the membrane potential v.
+    pub c: f64,
+    /// After-spike reset value of recovery variable u.
+    pub d: f64,
+    /// Membrane potential recovery variable.
+    pub u: f64,
+    /// Membrane potential/voltage.
+    pub v: f64,
+    /// Extended variables (optional).
+    pub ext: Option<NeuriteExt>,
+    /// The integration method.
+    pub integrator: Integrator,
+    /// The neurite IDs of the children, in order.
+    pub children: Vec<NeuriteID>,
+    /// The indices of the attached electrodes, in order.
+    pub electrodes: Vec<usize>,
+    /// The synaptic IDs of the attached synapses, in order.
+    pub synapses: Vec<SynapticID>
+}
+
+/// Contains a list of long-term plasticity records.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum LtpRecord {
+    /// Spike-timing-dependent plasticity.
+    Stdp(SpikeTimingDependentPlasticity),
+    /// Reward-modulated STDP with the specified eligibility trace decay time and dopamine signal index.
+    RewardModulated(SpikeTimingDependentPlasticity, f64, usize)
+}
+
+/// Contains data for a synapse in a model file. Fields a synapse type does not have are 0 (or none).
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SynapseRecord {
+    /// The synaptic ID.
+    pub id: SynapticID,
+    /// The type of synapse.
+    pub syn_type: SynapseType,
+    /// The presynaptic input.
+    pub x_pre: InputRecord,
+    /// The neurite ID of the postsynaptic neurite.
+    pub x_post: NeuriteID,
+    /// The synaptic ID of the synaptic modulatory synapse (optional) connected to the synapse.
+    pub mod_syn: Option<SynapticID>,
+    /// Short-term plasticity data (none for a gap junction).
+    pub stp: Option<ShortTermPlasticity>,
+    /// Long-term plasticity data (optional).
+    pub ltp: Option<LtpRecord>,
+    /// Modulation of the target variable of a modulatory synapse (optional).
+    pub modulation: Option<Modulation>,
+    /// Conduction delay of presynaptic spikes (ms).
+    pub delay: f64,
+    /// Conductance decay time.
+    pub tx: f64,
+    /// The shunting scalar.
+    pub s: f64,
+    /// The reversal potential of a shunting inhibitory synapse (mV).
+    pub e_rev: f64,
+    /// Synaptic weight.
+    pub w: f64,
+    /// Maximal synaptic input conductance.
+    pub x_max: f64
+}
+
+// SynapseRecord functions
+impl SynapseRecord {
+    /// Creates a new synapse record with the specified references, and every other field 0 (or none).
+    pub fn new(id: SynapticID, syn_type: SynapseType, x_pre: InputRecord, x_post: NeuriteID,
+               mod_syn: Option<SynapticID>) -> Self {
+        Self {
+            id,
+            syn_type,
+            x_pre,
+            x_post,
+            mod_syn,
+            stp: None,
+            ltp: None,
+            modulation: None,
+            delay: 0.0,
+            tx: 0.0,
+            s: 0.0,
+            e_rev: 0.0,
+            w: 0.0,
+            x_max: 0.0
+        }
+    }
+}
+
+/// Contains a list of electrode records.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum ElectrodeRecord {
+    Pulse(PulseElectrode),
+    Command(CommandElectrode),
+    Pulsating(PulsatingElectrode),
+    Sinusoidal(SinusoidalElectrode),
+    Poisson(PoissonElectrode),
+    WhiteNoise(WhiteNoiseElectrode),
+    OrnsteinUhlenbeck(OrnsteinUhlenbeckElectrode),
+    ShotNoise(ShotNoiseElectrode),
+    Waveform(WaveformElectrode),
+    /// Voltage clamp of the specified neurite with the specified holding potential, step commands and gain.
+    VoltageClamp(NeuriteID, f64, Vec<(f64, f64, f64)>, f64),
+    /// Conductance of the specified electrode index with the specified reversal potential, into the specified neurite.
+    Conductance(usize, f64, NeuriteID)
+}
+
+/// Contains data for a dopamine signal in a model file.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct DopamineRecord {
+    /// Dopamine uptake (decay) time.
+    pub td: f64,
+    /// Tonic dopamine concentration.
+    pub tonic: f64,
+    /// Presynaptic input (optional) that releases dopamine when it spikes.
+    pub source: Option<InputRecord>,
+    /// Dopamine released per presynaptic spike.
+    pub release: f64
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains the parts of a model: neurons, standalone neurites, synapses, electrodes and dopamine signals.
+pub struct Model {
+    /// The neurons.
+    pub neurons: Vec<Neuron>,
+    /// The standalone neurites (neurites that are not part of a neuron).
+    pub neurites: Vec<Rc<RefCell<Neurite>>>,
+    /// The synapses.
+    pub synapses: Vec<Rc<RefCell<dyn Synapse>>>,
+    /// The electrodes.
+    pub electrodes: Vec<Rc<RefCell<dyn Electrode>>>,
+    /// The electrodes that are not in the electrode list, such as electrodes attached to neurites or providing the
+    /// conductance of a conductance electrode. This is only filled by building a model file, and is not saved.
+    pub other_electrodes: Vec<Rc<RefCell<dyn Electrode>>>,
+    /// The dopamine signals.
+    pub dopamine: Vec<Rc<RefCell<DopamineSignal>>>
+}
+
+/// Contains the shared references found while recording a model. Each reference is recorded once and referred to by
+/// its ID (neurites and synapses) or index (electrodes and dopamine signals).
+#[derive(Default)]
+pub struct ModelRefs {
+    /// The neurites found.
+    neurites: Vec<Rc<RefCell<Neurite>>>,
+    /// The synapses found.
+    synapses: Vec<Rc<RefCell<dyn Synapse>>>,
+    /// The electrodes found.
+    electrodes: Vec<Rc<RefCell<dyn Electrode>>>,
+    /// The dopamine signals found.
+    dopamine: Vec<Rc<RefCell<DopamineSignal>>>
+}
+
+// ModelRefs functions
+impl ModelRefs {
+    /// Returns the neurite ID of the specified neurite, adding it to the references if it is new.
+    pub fn neurite(&mut self, neurite: &Rc<RefCell<Neurite>>) -> NeuriteID {
+        if !self.neurites.iter().any(|n| Rc::ptr_eq(n, neurite)) {
+            self.neurites.push(Rc::clone(neurite));
+        }
+
+        neurite.borrow().id()
+    }
+
+    /// Returns the synaptic ID of the specified synapse, adding it to the references if it is new.
+    pub fn synapse(&mut self, synapse: &Rc<RefCell<dyn Synapse>>) -> SynapticID {
+        let id = synapse.borrow().syn_id();
+
+        if !self.synapses.iter().any(|s| s.borrow().syn_id() == id) {
+            self.synapses.push(Rc::clone(synapse));
+        }
+
+        id
+    }
+
+    /// Returns the synaptic ID of the specified synaptic modulatory synapse (optional), adding it to the references if
+    /// it is new.
+    pub fn mod_syn(&mut self, mod_syn: &Option<Rc<RefCell<SynapticModulatorySynapse>>>) -> Option<SynapticID> {
+        mod_syn.as_ref().map(|m| {
+            let m: Rc<RefCell<dyn Synapse>> = Rc::clone(m) as Rc<RefCell<dyn Synapse>>;
+            self.synapse(&m)
+        })
+    }
+
+    /// Returns the index of the specified electrode, adding it to the references if it is new.
+    pub fn electrode(&mut self, electrode: &Rc<RefCell<dyn Electrode>>) -> usize {
+        let ptr = Rc::as_ptr(electrode) as *const u8;
+
+        match self.electrodes.iter().position(|e| Rc::as_ptr(e) as *const u8 == ptr) {
+            Some(i) => i,
+            None => {
+                self.electrodes.push(Rc::clone(electrode));
+                self.electrodes.len() - 1
+            }
+        }
+    }
+
+    /// Returns the index of the specified dopamine signal, adding it to the references if it is new.
+    pub fn dopamine(&mut self, dopamine: &Rc<RefCell<DopamineSignal>>) -> usize {
+        match self.dopamine.iter().position(|d| Rc::ptr_eq(d, dopamine)) {
+            Some(i) => i,
+            None => {
+                self.dopamine.push(Rc::clone(dopamine));
+                self.dopamine.len() - 1
+            }
+        }
+    }
+
+    /// Returns the record of the specified presynaptic input. A sensor input cannot be recorded.
+    pub fn input(&mut self, input: &PresynapticInput) -> Result<InputRecord, String> {
+        match input {
+            PresynapticInput::Electrode(e) => Ok(InputRecord::Electrode(self.electrode(e))),
+            PresynapticInput::Neurite(n) => Ok(InputRecord::Neurite(self.neurite(n))),
+            PresynapticInput::Sensor(_) => Err("Cannot save a sensor presynaptic input.".to_string())
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a versioned model file. Shared references (neurites, synapses, electrodes and dopamine signals)
+/// are recorded once and referred to by ID or index, and are reconstructed as shared references on load.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ModelFile {
+    /// The model file format version.
+    version: u32,
+    /// The neurite IDs of the somas of the model's neurons.
+    neurons: Vec<NeuriteID>,
+    /// The neurite IDs of the model's standalone neurites.
+    neurites: Vec<NeuriteID>,
+    /// The synaptic IDs of the model's synapses.
+    synapses: Vec<SynapticID>,
+    /// The indices of the model's electrodes.
+    electrodes: Vec<usize>,
+    /// The number of the model's dopamine signals (the first dopamine records).
+    dopamine: usize,
+    /// Every neurite, including neurites only referenced by other records.
+    neurite_records: Vec<NeuriteRecord>,
+    /// Every synapse, including synapses only attached to neurites or connected as synaptic modulators.
+    synapse_records: Vec<SynapseRecord>,
+    /// Every electrode, including electrodes only attached to neurites or used as inputs.
+    electrode_records: Vec<ElectrodeRecord>,
+    /// Every dopamine signal.
+    dopamine_records: Vec<DopamineRecord>
+}
+
+// ModelFile functions
+impl ModelFile {
+    /// Creates a new model file that records the specified model. Every reference reachable from the model is
+    /// recorded. If a part of the model cannot be recorded, this returns an error message specifying why.
+    pub fn new(model: &Model) -> Result<Self, String> {
+        let mut refs = ModelRefs::default();
+
+        for d in &model.dopamine {
+            refs.dopamine(d);
+        }
+
+        let electrodes = model.electrodes.iter().map(|e| refs.electrode(e)).collect();
+        let neurons = model.neurons.iter().map(|n| refs.neurite(&n.soma())).collect();
+        let neurites = model.neurites.iter().map(|n| refs.neurite(n)).collect();
+        let synapses = model.synapses.iter().map(|s| refs.synapse(s)).collect();
+
+        let mut neurite_records: Vec<NeuriteRecord> = vec![];
+        let mut synapse_records: Vec<SynapseRecord> = vec![];
+        let mut electrode_records: Vec<ElectrodeRecord> = vec![];
+        let mut dopamine_records: Vec<DopamineRecord> = vec![];
+
+        // Recording a reference can find new references, so record until every reference found is recorded
+        loop {
+            if neurite_records.len() < refs.neurites.len() {
+                let n = Rc::clone(&refs.neurites[neurite_records.len()]);
+                neurite_records.push(n.borrow().record(&mut refs));
+            }
+            else if synapse_records.len() < refs.synapses.len() {
+                let s = Rc::clone(&refs.synapses[synapse_records.len()]);
+                synapse_records.push(s.borrow().record(&mut refs)?);
+            }
+            else if electrode_records.len() < refs.electrodes.len() {
+                let e = Rc::clone(&refs.electrodes[electrode_records.len()]);
+                electrode_records.push(e.borrow().record(&mut refs).ok_or("Cannot save an electrode of this type.")?);
+            }
+            else if dopamine_records.len() < refs.dopamine.len() {
+                let d = Rc::clone(&refs.dopamine[dopamine_records.len()]);
+                dopamine_records.push(d.borrow().record(&mut refs)?);
+            }
+            else {
+                break;
+            }
+        }
+
+        Ok(Self {
+            version: MODEL_VERSION,
+            neurons,
+            neurites,
+            synapses,
+            electrodes,
+            dopamine: model.dopamine.len(),
+            neurite_records,
+            synapse_records,
+            electrode_records,
+            dopamine_records
+        })
+    }
+
+    /// Returns the model file format version.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Saves the model file to the specified path in the specified format.
+    pub fn save(&self, path: &str, format: ModelFormat) -> Result<(), String> {
+        write_file(path, self, format, "model")
+    }
+
+    /// Loads a model file from the specified path in the specified format. Files with a newer format version than
+    /// this program supports are rejected.
+    pub fn load(path: &str, format: ModelFormat) -> Result<Self, String> {
+        let file: Self = read_file(path, format, "model")?;
+
+        if file.version > MODEL_VERSION {
+            return Err(format!("Model file {} has version {}, but only versions up to {} are supported.", path,
+                               file.version, MODEL_VERSION));
+        }
+
+        Ok(file)
+    }
+
+    /// Builds the recorded model, reconstructing every shared reference. If a record refers to a missing reference,
+    /// this returns an error message specifying why.
+    pub fn build(&self) -> Result<Model, String> {
+        // Neurites and the neurite tree
+        let neurites: Vec<Rc<RefCell<Neurite>>> = self.neurite_records.iter()
+            .map(|r| Rc::new(RefCell::new(Neurite::from_record(r))))
+            .collect();
+        let neurite = |id: NeuriteID| -> Result<Rc<RefCell<Neurite>>, String> {
+            self.neurite_records.iter()
+                .position(|r| r.id == id)
+                .map(|i| Rc::clone(&neurites[i]))
+                .ok_or_else(|| "Model file refers to a missing neurite.".to_string())
+        };
+
+        for (r, n) in self.neurite_records.iter().zip(&neurites) {
+            for &child in &r.children {
+                n.borrow_mut().add_child(neurite(child)?)?;
+            }
+        }
+
+        // Electrodes (a conductance electrode is built after the electrode it refers to)
+        let mut electrodes: Vec<Option<Rc<RefCell<dyn Electrode>>>> = vec![None; self.electrode_records.len()];
+
+        while electrodes.iter().any(Option::is_none) {
+            let mut progress = false;
+
+            for (i, r) in self.electrode_records.iter().enumerate() {
+                if electrodes[i].is_some() {
+                    continue;
+                }
+
+                let e: Rc<RefCell<dyn Electrode>> = match r {
+                    ElectrodeRecord::Pulse(e) => Rc::new(RefCell::new(e.clone())),
+                    ElectrodeRecord::Command(e) => Rc::new(RefCell::new(e.clone())),
+                    ElectrodeRecord::Pulsating(e) => Rc::new(RefCell::new(e.clone())),
+                    ElectrodeRecord::Sinusoidal(e) => Rc::new(RefCell::new(e.clone())),
+                    ElectrodeRecord::Poisson(e) => Rc::new(RefCell::new(e.clone())),
+                    ElectrodeRecord::WhiteNoise(e) => Rc::new(RefCell::new(e.clone())),
+                    ElectrodeRecord::OrnsteinUhlenbeck(e) => Rc::new(RefCell::new(e.clone())),
+                    ElectrodeRecord::ShotNoise(e) => Rc::new(RefCell::new(e.clone())),
+                    ElectrodeRecord::Waveform(e) => {
+                        e.check()?;
+                        Rc::new(RefCell::new(e.clone()))
+                    }
+                    ElectrodeRecord::VoltageClamp(target, v, commands, gain) => {
+                        let mut e = VoltageClampElectrode::new(&neurite(*target)?, *v, *gain);
+
+                        for &(start, duration, v) in commands {
+                            e.add_command(start, duration, v);
+                        }
+
+                        Rc::new(RefCell::new(e))
+                    }
+                    ElectrodeRecord::Conductance(g, e_rev, target) => {
+                        match electrodes.get(*g).ok_or("Model file refers to a missing electrode.")? {
+                            Some(g) => Rc::new(RefCell::new(ConductanceElectrode::new(Rc::clone(g), *e_rev, &neurite(*target)?))),
+                            None => continue
+                        }
+                    }
+                };
+
+                electrodes[i] = Some(e);
+                progress = true;
+            }
+
+            if !progress {
+                return Err("Model file has conductance electrodes that refer to each other.".to_string());
+            }
+        }
+
+        let electrodes: Vec<Rc<RefCell<dyn Electrode>>> = electrodes.into_iter().flatten().collect();
+        let electrode = |i: usize| -> Result<Rc<RefCell<dyn Electrode>>, String> {
+            electrodes.get(i).cloned().ok_or_else(|| "Model file refers to a missing electrode.".to_string())
+        };
+        let input = |r: &InputRecord| -> Result<PresynapticInput, String> {
+            match r {
+                InputRecord::Electrode(i) => Ok(PresynapticInput::Electrode(electrode(*i)?)),
+                InputRecord::Neurite(id) => Ok(PresynapticInput::Neurite(neurite(*id)?))
+            }
+        };
+
+        for (r, n) in self.neurite_records.iter().zip(&neurites) {
+            for &i in &r.electrodes {
+                n.borrow_mut().attach_electrode(electrode(i)?);
+            }
+        }
+
+        // Dopamine signals
+        let mut dopamine: Vec<Rc<RefCell<DopamineSignal>>> = vec![];
+
+        for r in &self.dopamine_records {
+            let mut d = DopamineSignal::new(r.td, r.tonic);
+            d.set_source(r.source.as_ref().map(&input).transpose()?, r.release);
+            dopamine.push(Rc::new(RefCell::new(d)));
+        }
+
+        // Synapses (synaptic modulatory synapses are kept as their concrete type to connect them to other synapses)
+        let mut synapses: Vec<Rc<RefCell<dyn Synapse>>> = vec![];
+        let mut mod_syns: Vec<Rc<RefCell<SynapticModulatorySynapse>>> = vec![];
+
+        for r in &self.synapse_records {
+            let x_pre = input(&r.x_pre)?;
+            let x_post = neurite(r.x_post)?;
+
+            // Only chemical synapses have short-term plasticity
+            if r.stp.is_some() == (r.syn_type == SynapseType::Gap) {
+                return Err("Model file has a synapse with mismatched short-term plasticity data.".to_string());
+            }
+
+            let ltp = match &r.ltp {
+                Some(LtpRecord::Stdp(stdp)) => Some(LongTermPlasticity::Stdp(stdp.clone())),
+                Some(LtpRecord::RewardModulated(stdp, tc, d)) => {
+                    let d = dopamine.get(*d).ok_or("Model file refers to a missing dopamine signal.")?;
+                    Some(LongTermPlasticity::RewardModulated(RewardModulatedPlasticity::new(stdp.clone(), *tc, Rc::clone(d))))
+                }
+                None => None
+            };
+
+            let s: Rc<RefCell<dyn Synapse>> = match r.syn_type {
+                SynapseType::Excitatory => Rc::new(RefCell::new(ExcitatorySynapse::from_record(r, x_pre, x_post, ltp))),
+                SynapseType::Inhibitory => Rc::new(RefCell::new(InhibitorySynapse::from_record(r, x_pre, x_post, ltp))),
+                SynapseType::ShuntingInhibitory => {
+                    Rc::new(RefCell::new(ShuntingInhibitorySynapse::from_record(r, x_pre, x_post, ltp)))
+                }
+                SynapseType::SynapticModulator(_) => {
+                    let m = Rc::new(RefCell::new(SynapticModulatorySynapse::from_record(r, x_pre, x_post)));
+                    mod_syns.push(Rc::clone(&m));
+                    m
+                }
+                SynapseType::NeuralModulator(_) => {
+                    Rc::new(RefCell::new(NeuralModulatorySynapse::from_record(r, x_pre, x_post)))
+                }
+                SynapseType::Gap => Rc::new(RefCell::new(GapJunctionSynapse::from_record(r, x_pre, x_post)))
+            };
+
+            synapses.push(s);
+        }
+
+        let synapse = |id: SynapticID| -> Result<Rc<RefCell<dyn Synapse>>, String> {
+            self.synapse_records.iter()
+                .position(|r| r.id == id)
+                .map(|i| Rc::clone(&synapses[i]))
+                .ok_or_else(|| "Model file refers to a missing synapse.".to_string())
+        };
+
+        for (r, s) in self.synapse_records.iter().zip(&synapses) {
+            if let Some(id) = r.mod_syn {
+                let m = mod_syns.iter()
+                    .find(|m| m.borrow().syn_id() == id)
+                    .ok_or("Model file refers to a missing synaptic modulatory synapse.")?;
+                s.borrow_mut().set_mod_syn(Some(Rc::clone(m)));
+            }
+        }
+
+        for (r, n) in self.neurite_records.iter().zip(&neurites) {
+            for &id in &r.synapses {
+                n.borrow_mut().attach_synapse(synapse(id)?)?;
+            }
+        }
+
+        Ok(Model {
+            neurons: self.neurons.iter()
+                .map(|&id| Neuron::from_soma(neurite(id)?).map_err(String::from))
+                .collect::<Result<_, _>>()?,
+            neurites: self.neurites.iter().map(|&id| neurite(id)).collect::<Result<_, _>>()?,
+            synapses: self.synapses.iter().map(|&id| synapse(id)).collect::<Result<_, _>>()?,
+            electrodes: self.electrodes.iter().map(|&i| electrode(i)).collect::<Result<_, _>>()?,
+            other_electrodes: electrodes.iter()
+                .enumerate()
+                .filter(|(i, _)| !self.electrodes.contains(i))
+                .map(|(_, e)| Rc::clone(e))
+                .collect(),
+            dopamine: dopamine.iter().take(self.dopamine).cloned().collect()
+        })
+    }
+}
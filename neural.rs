@@ -1,15 +1,18 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fs;
 use std::f64::consts::PI;
 use std::ops::{Deref, DerefMut};
-use std::rc::Rc;
-use std::time::SystemTime;
-use num_traits::Pow;
-use rand::random;
-use uuid::{Timestamp, Uuid};
-use crate::system::TIME_INC;
+use std::rc::{Rc, Weak};
+use rand::{Rng, SeedableRng};
+use rand_distr::StandardNormal;
+use uuid::Uuid;
+use crate::checkpoint::{ElectrodeState, LtpState, NeuriteState, SensorState, SynapseState};
+use crate::model::{DopamineRecord, ElectrodeRecord, LtpRecord, ModelRefs, NeuriteRecord, SynapseRecord};
+use crate::system::SimRng;
 
 /// Contains a list of synaptic variables that can be modulated by a modulatory synapse.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum SynapticModVar {
     G,
     X,
@@ -19,7 +22,7 @@ pub enum SynapticModVar {
 }
 
 /// Contains a list of neurite variables that can be modulated by a modulatory synapse.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum NeuriteModVar {
     A,
     B,
@@ -34,25 +37,59 @@ pub enum NeuriteModVar {
     CAP
 }
 
-/// Contains the synaptic ID value for a synapse.
+/// Contains a list of neurite variables that can be recorded by a probe.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NeuriteVar {
+    U,
+    V,
+    Y,
+    VCC,
+    VPC
+}
+
+/// Contains a list of synaptic variables that can be recorded by a probe.
 #[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SynapseVar {
+    X,
+    AX,
+    BX,
+    W,
+    STPF,
+    STPD,
+    STPY
+}
+
+/// Contains the synaptic ID value for a synapse.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct SynapticID {
     /// The synapse's ID value.
     id: Uuid,
 }
 
+// Default for SynapticID
+impl Default for SynapticID {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // SynapticID function
 impl SynapticID {
     /// Creates a new unique synaptic ID value.
     pub fn new() -> Self {
         Self {
-            id: Uuid::new_v7(Timestamp::now(SystemTime::now()))
+            id: Uuid::now_v7()
         }
     }
+
+    /// Returns the UUID of the synaptic ID.
+    pub fn uuid(&self) -> Uuid {
+        self.id
+    }
 }
 
 /// Contains a list of synapse types.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum SynapseType {
     Excitatory,
     Inhibitory,
@@ -63,13 +100,65 @@ pub enum SynapseType {
 }
 
 /// Contains a list of presynaptic input types.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone)]
 pub enum PresynapticInput {
     Electrode(Rc<RefCell<dyn Electrode>>),
     Sensor(Rc<RefCell<dyn Sensor>>),
     Neurite(Rc<RefCell<Neurite>>)
 }
 
+// Debug for PresynapticInput
+impl std::fmt::Debug for PresynapticInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PresynapticInput::Electrode(_) => write!(f, "Electrode"),
+            PresynapticInput::Sensor(_) => write!(f, "Sensor"),
+            PresynapticInput::Neurite(n) => write!(f, "Neurite({:?})", n.borrow().id)
+        }
+    }
+}
+
+// PartialEq for PresynapticInput (inputs are equal if they are the same shared input)
+impl PartialEq for PresynapticInput {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (PresynapticInput::Electrode(a), PresynapticInput::Electrode(b)) => Rc::ptr_eq(a, b),
+            (PresynapticInput::Sensor(a), PresynapticInput::Sensor(b)) => Rc::ptr_eq(a, b),
+            (PresynapticInput::Neurite(a), PresynapticInput::Neurite(b)) => Rc::ptr_eq(a, b),
+            _ => false
+        }
+    }
+}
+
+// PresynapticInput functions
+impl PresynapticInput {
+    /// Returns the neurite ID of the presynaptic input, or none if the input is not a neurite.
+    pub fn neurite_id(&self) -> Option<NeuriteID> {
+        match self {
+            PresynapticInput::Neurite(n) => Some(n.borrow().id),
+            _ => None
+        }
+    }
+
+    /// Returns true if the presynaptic input is currently spiking (has a positive output).
+    pub fn spike(&self) -> bool {
+        match self {
+            PresynapticInput::Electrode(e) => e.borrow().output() > 0.0,
+            PresynapticInput::Sensor(s) => s.borrow().output() > 0.0,
+            PresynapticInput::Neurite(n) => n.borrow().y > 0.0
+        }
+    }
+
+    /// Returns the current output of the presynaptic input. For a neurite this is its membrane potential.
+    pub fn value(&self) -> f64 {
+        match self {
+            PresynapticInput::Electrode(e) => e.borrow().output(),
+            PresynapticInput::Sensor(s) => s.borrow().output(),
+            PresynapticInput::Neurite(n) => n.borrow().v
+        }
+    }
+}
+
 /// Trait for any type of electrode.
 pub trait Electrode {
     /// Turns on (activates) the electrode.
@@ -81,20 +170,54 @@ pub trait Electrode {
     /// Returns the voltage input of the electrode.
     fn voltage(&self) -> f64;
 
+    /// Sets the voltage input of the electrode.
+    fn set_voltage(&mut self, v: f64);
+
     /// Returns the duration of the electrode's pulse.
     fn duration(&self) -> f64;
 
     /// Returns the current output of the electrode.
     fn output(&self) -> f64;
 
-    /// Process and returns the next output of the electrode.
-    fn process(&mut self) -> f64;
+    /// Process and returns the next output of the electrode after the specified time step (ms).
+    fn process(&mut self, time: f64) -> f64;
+
+    /// Seeds the electrode's random number generator. Electrodes without random output ignore this.
+    fn seed(&mut self, _rng: SimRng) {}
+
+    /// Returns the model file record of the electrode, or none if the electrode cannot be saved.
+    fn record(&self, _refs: &mut ModelRefs) -> Option<ElectrodeRecord> {
+        None
+    }
+
+    /// Returns the dynamic state of the electrode, or none if the electrode cannot be checkpointed.
+    fn state(&self) -> Option<ElectrodeState> {
+        None
+    }
+
+    /// Restores the dynamic state of the electrode. If the state is not of this type of electrode, this returns an
+    /// error message.
+    fn restore(&mut self, _state: &ElectrodeState) -> Result<(), &'static str> {
+        Err("Cannot restore an electrode of this type.")
+    }
 }
 
 /// Trait for any type of sensor.
 pub trait Sensor {
     /// Returns the current output of the sensor.
     fn output(&self) -> f64;
+
+    /// Processes and returns the next output of the sensor after the specified time step (ms).
+    fn process(&mut self, time: f64) -> f64;
+
+    /// Seeds the sensor's random number generator. Sensors without random output ignore this.
+    fn seed(&mut self, _rng: SimRng) {}
+
+    /// Returns the dynamic state of the sensor.
+    fn state(&self) -> SensorState;
+
+    /// Restores the dynamic state of the sensor.
+    fn restore(&mut self, state: &SensorState);
 }
 
 /// Trait for any type of synapse.
@@ -111,22 +234,188 @@ pub trait Synapse {
     /// Returns the current total synaptic input.
     fn input(&self) -> f64;
 
-    /// Processes and returns the next total input of the synapse.
-    fn process(&mut self) -> f64;
+    /// Returns the current synaptic input delivered to the specified neurite.
+    fn input_to(&self, _id: NeuriteID) -> f64 {
+        self.input()
+    }
+
+    /// Sets the synaptic modulatory synapse (optional) connected to this synapse.
+    fn set_mod_syn(&mut self, mod_syn: Option<Rc<RefCell<SynapticModulatorySynapse>>>);
+
+    /// Returns the current value of the specified variable, or none if the synapse does not have the variable.
+    fn probe(&self, var: SynapseVar) -> Option<f64>;
+
+    /// Returns the model file record of the synapse. If the synapse cannot be saved, this returns an error message
+    /// specifying why.
+    fn record(&self, refs: &mut ModelRefs) -> Result<SynapseRecord, String>;
+
+    /// Returns the dynamic state of the synapse.
+    fn state(&self) -> SynapseState;
+
+    /// Restores the dynamic state of the synapse.
+    fn restore(&mut self, state: &SynapseState);
+
+    /// Processes and returns the next total input of the synapse after the specified time step (ms).
+    fn process(&mut self, time: f64) -> f64;
+}
+
+/// Implements the model file record and checkpoint state functions of an electrode whose dynamic state is the complete
+/// electrode, for the specified model file record variant. The listed runtime fields are reset to the specified values
+/// in the model file record.
+macro_rules! electrode_functions {
+    ($variant:ident; $($field:ident: $value:expr),*) => {
+        /// Returns the model file record of the electrode (without its runtime state).
+        fn record(&self, _refs: &mut ModelRefs) -> Option<ElectrodeRecord> {
+            Some(ElectrodeRecord::$variant(Self { $($field: $value,)* ..self.clone() }))
+        }
+
+        /// Returns the dynamic state of the electrode (the complete electrode).
+        fn state(&self) -> Option<ElectrodeState> {
+            Some(ElectrodeState::Record(Box::new(ElectrodeRecord::$variant(self.clone()))))
+        }
+
+        /// Restores the dynamic state of the electrode. If the state is not of this type of electrode, this returns an
+        /// error message.
+        fn restore(&mut self, state: &ElectrodeState) -> Result<(), &'static str> {
+            match state {
+                ElectrodeState::Record(record) => match &**record {
+                    ElectrodeRecord::$variant(e) => {
+                        *self = e.clone();
+                        Ok(())
+                    }
+                    _ => Err("Electrode state does not match the electrode type.")
+                },
+                _ => Err("Electrode state does not match the electrode type.")
+            }
+        }
+    };
+}
+
+/// Implements the model file record and checkpoint state functions of a synapse. The record has the listed parameters
+/// and the state the listed variables. The listed parts (short-term plasticity, conduction delay, long-term plasticity
+/// and modulation) are saved where they apply.
+macro_rules! synapse_functions {
+    (record: [$($param:ident),*], state: [$($var:ident),*], parts: [$($part:ident),*]) => {
+        /// Returns the model file record of the synapse.
+        fn record(&self, refs: &mut ModelRefs) -> Result<SynapseRecord, String> {
+            let mut record = SynapseRecord::new(self.id, self.syn_type, refs.input(&self.x_pre)?, refs.neurite(&self.x_post),
+                                                refs.mod_syn(&self.mod_syn));
+            $(record.$param = self.$param;)*
+            $(synapse_functions!(@record self record refs $part);)*
+
+            Ok(record)
+        }
+
+        /// Returns the dynamic state of the synapse.
+        fn state(&self) -> SynapseState {
+            let mut state = SynapseState::new(self.id);
+            $(state.$var = self.$var;)*
+            $(synapse_functions!(@state self state $part);)*
+
+            state
+        }
+
+        /// Restores the dynamic state of the synapse.
+        fn restore(&mut self, state: &SynapseState) {
+            $(self.$var = state.$var;)*
+            $(synapse_functions!(@restore self state $part);)*
+        }
+    };
+    (@record $self:ident $record:ident $refs:ident stp) => { $record.stp = Some($self.stp.clone()); };
+    (@record $self:ident $record:ident $refs:ident delay) => { $record.delay = $self.delay.delay(); };
+    (@record $self:ident $record:ident $refs:ident ltp) => { $record.ltp = $self.ltp.as_ref().map(|ltp| ltp.record($refs)); };
+    (@record $self:ident $record:ident $refs:ident modulation) => { $record.modulation = Some($self.modulation); };
+    (@state $self:ident $state:ident stp) => { $state.stp = Some($self.stp.clone()); };
+    (@state $self:ident $state:ident delay) => { $state.delay = Some($self.delay.clone()); };
+    (@state $self:ident $state:ident ltp) => { $state.ltp = $self.ltp.as_ref().map(|ltp| ltp.state()); };
+    (@state $self:ident $state:ident modulation) => {};
+    (@restore $self:ident $state:ident stp) => {
+        if let Some(stp) = &$state.stp {
+            $self.stp = stp.clone();
+        }
+    };
+    (@restore $self:ident $state:ident delay) => {
+        if let Some(delay) = &$state.delay {
+            $self.delay = delay.clone();
+        }
+    };
+    (@restore $self:ident $state:ident ltp) => {
+        if let (Some(ltp), Some(s)) = ($self.ltp.as_mut(), &$state.ltp) {
+            ltp.restore(s);
+        }
+    };
+    (@restore $self:ident $state:ident modulation) => {};
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for the conduction delay of presynaptic spikes.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SpikeDelay {
+    /// The conduction delay (ms).
+    delay: f64,
+    /// The remaining delivery time of each spike in transit (ms), in order of delivery.
+    queue: VecDeque<f64>
+}
+
+// SpikeDelay functions
+impl SpikeDelay {
+    /// Creates new spike delay data with the specified conduction delay (ms).
+    pub fn new(delay: f64) -> Self {
+        Self {
+            delay: delay.max(0.0),
+            queue: VecDeque::new()
+        }
+    }
+
+    /// Returns the conduction delay (ms).
+    pub fn delay(&self) -> f64 {
+        self.delay
+    }
+
+    /// Returns the number of spikes in transit.
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Queues the specified presynaptic spike and returns true if a queued spike is delivered after the specified
+    /// time step (ms). Spikes are delivered on the time step nearest to their delay expiring.
+    pub fn process(&mut self, spike: bool, time: f64) -> bool {
+        for t in self.queue.iter_mut() {
+            *t -= time;
+        }
+
+        if spike {
+            self.queue.push_back(self.delay);
+        }
+
+        let mut delivered = false;
+
+        while let Some(&t) = self.queue.front() {
+            if t >= 0.5 * time {
+                break;
+            }
+
+            self.queue.pop_front();
+            delivered = true;
+        }
+
+        delivered
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
 
 /// Contains data for short-term synaptic plasticity.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct ShortTermPlasticity {
     /// Short-term facilitation scalar.
     f: f64,
-    /// Short-term facilitation recovery time.
+    /// Short-term facilitation recovery time (ms).
     tf: f64,
     /// Short-term depression scalar.
     d: f64,
-    /// Short-term depression decay time.
+    /// Short-term depression decay time (ms).
     td: f64,
     /// Short-term plasticity increment/decrement value.
     p: f64,
@@ -136,7 +425,8 @@ pub struct ShortTermPlasticity {
 
 // ShortTermPlasticity functions
 impl ShortTermPlasticity {
-    /// Creates new short-term plasticity data.
+    /// Creates new short-term plasticity data. The recovery and decay times are in ms. They used to be counted in time
+    /// steps, so times set for the former fixed 0.1 ms time step are 10 times the times in ms.
     pub fn new(tf: f64, td: f64, p: f64) -> Self {
         Self {
             f: 1.0,
@@ -148,9 +438,10 @@ impl ShortTermPlasticity {
         }
     }
 
-    /// Processes the short-term plasticity based on the specified spike input and returns the new short-term
-    /// plasticity weight output.
-    pub fn learn(&mut self, spike: bool) -> f64 {
+    /// Processes the short-term plasticity based on the specified spike input and time step (ms) and returns the
+    /// new short-term plasticity weight output. Between spikes, facilitation and depression relax exactly
+    /// (exponentially), so the result does not depend on the time step.
+    pub fn learn(&mut self, spike: bool, time: f64) -> f64 {
         let pd = self.d;
 
         if spike {
@@ -158,19 +449,442 @@ impl ShortTermPlasticity {
             self.d -= self.f * self.d;
         }
         else {
-            self.f -= self.f / self.tf;
-            self.d += (1.0 - self.d) / self.td;
+            self.f *= (-time / self.tf).exp();
+            self.d = 1.0 - (1.0 - self.d) * (-time / self.td).exp();
         }
 
         self.y = self.f * pd;
         self.y
     }
+
+    /// Returns the current value of the specified short-term plasticity variable, or none if the variable is not a
+    /// short-term plasticity variable.
+    pub fn probe(&self, var: SynapseVar) -> Option<f64> {
+        match var {
+            SynapseVar::STPF => Some(self.f),
+            SynapseVar::STPD => Some(self.d),
+            SynapseVar::STPY => Some(self.y),
+            _ => None
+        }
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
 
-/// Contains data for a pulse electrode.
+/// Contains a list of spike-timing-dependent plasticity rules.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum StdpRule {
+    /// Pair-based rule (nearest-neighbour independent, all-to-all traces).
+    Pair,
+    /// Triplet rule (Pfister & Gerstner, 2006).
+    Triplet
+}
+
+/// Contains data for long-term spike-timing-dependent plasticity (STDP).
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SpikeTimingDependentPlasticity {
+    /// The STDP rule.
+    rule: StdpRule,
+    /// Pair potentiation amplitude (A+).
+    a_plus: f64,
+    /// Pair depression amplitude (A-).
+    a_minus: f64,
+    /// Triplet potentiation amplitude (A3+).
+    a3_plus: f64,
+    /// Triplet depression amplitude (A3-).
+    a3_minus: f64,
+    /// Potentiation time constant (tau+).
+    tau_plus: f64,
+    /// Depression time constant (tau-).
+    tau_minus: f64,
+    /// Slow presynaptic trace time constant (tau x).
+    tau_x: f64,
+    /// Slow postsynaptic trace time constant (tau y).
+    tau_y: f64,
+    /// Minimum synaptic weight.
+    w_min: f64,
+    /// Maximum synaptic weight.
+    w_max: f64,
+    /// Fast presynaptic trace.
+    r1: f64,
+    /// Slow presynaptic trace.
+    r2: f64,
+    /// Fast postsynaptic trace.
+    o1: f64,
+    /// Slow postsynaptic trace.
+    o2: f64
+}
+
+// SpikeTimingDependentPlasticity functions
+impl SpikeTimingDependentPlasticity {
+    /// Creates new pair-based STDP data with the specified parameters.
+    pub fn pair(a_plus: f64, a_minus: f64, tau_plus: f64, tau_minus: f64, w_min: f64, w_max: f64) -> Self {
+        Self {
+            rule: StdpRule::Pair,
+            a_plus,
+            a_minus,
+            a3_plus: 0.0,
+            a3_minus: 0.0,
+            tau_plus,
+            tau_minus,
+            tau_x: 1.0,
+            tau_y: 1.0,
+            w_min,
+            w_max,
+            r1: 0.0,
+            r2: 0.0,
+            o1: 0.0,
+            o2: 0.0
+        }
+    }
+
+    /// Creates new triplet STDP data with the specified parameters.
+    #[allow(clippy::too_many_arguments)]
+    pub fn triplet(a_plus: f64, a_minus: f64, a3_plus: f64, a3_minus: f64, tau_plus: f64, tau_minus: f64, tau_x: f64,
+                   tau_y: f64, w_min: f64, w_max: f64) -> Self {
+        Self {
+            rule: StdpRule::Triplet,
+            a_plus,
+            a_minus,
+            a3_plus,
+            a3_minus,
+            tau_plus,
+            tau_minus,
+            tau_x,
+            tau_y,
+            w_min,
+            w_max,
+            r1: 0.0,
+            r2: 0.0,
+            o1: 0.0,
+            o2: 0.0
+        }
+    }
+
+    /// Returns the STDP rule.
+    pub fn rule(&self) -> StdpRule {
+        self.rule
+    }
+
+    /// Processes the spike traces based on the specified presynaptic and postsynaptic spikes and time step (ms) and
+    /// returns the resulting (unbounded) weight change.
+    pub fn dw(&mut self, pre: bool, post: bool, time: f64) -> f64 {
+        self.r1 *= (-time / self.tau_plus).exp();
+        self.o1 *= (-time / self.tau_minus).exp();
+
+        if self.rule == StdpRule::Triplet {
+            self.r2 *= (-time / self.tau_x).exp();
+            self.o2 *= (-time / self.tau_y).exp();
+        }
+
+        let mut dw = 0.0;
+
+        // Presynaptic spike after postsynaptic spikes depresses the weight
+        if pre {
+            dw -= self.o1 * (self.a_minus + self.a3_minus * self.r2);
+        }
+
+        // Postsynaptic spike after presynaptic spikes potentiates the weight
+        if post {
+            dw += self.r1 * (self.a_plus + self.a3_plus * self.o2);
+        }
+
+        if pre {
+            self.r1 += 1.0;
+        }
+
+        if post {
+            self.o1 += 1.0;
+        }
+
+        // The slow traces are only kept by the triplet rule
+        if self.rule == StdpRule::Triplet {
+            if pre {
+                self.r2 += 1.0;
+            }
+
+            if post {
+                self.o2 += 1.0;
+            }
+        }
+
+        dw
+    }
+
+    /// Processes the STDP based on the specified presynaptic and postsynaptic spikes, current synaptic weight and time
+    /// step (ms) and returns the new synaptic weight.
+    pub fn learn(&mut self, pre: bool, post: bool, w: f64, time: f64) -> f64 {
+        (w + self.dw(pre, post, time)).max(self.w_min).min(self.w_max)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a dopamine (reward) signal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DopamineSignal {
+    /// Extracellular dopamine concentration.
+    d: f64,
+    /// Dopamine uptake (decay) time.
+    td: f64,
+    /// Tonic dopamine concentration.
+    tonic: f64,
+    /// Presynaptic input (optional) that releases dopamine when it spikes.
+    source: Option<PresynapticInput>,
+    /// Dopamine released per presynaptic spike.
+    release: f64
+}
+
+// DopamineSignal functions
+impl DopamineSignal {
+    /// Creates a new dopamine signal with the specified parameters.
+    pub fn new(td: f64, tonic: f64) -> Self {
+        Self {
+            d: 0.0,
+            td,
+            tonic,
+            source: None,
+            release: 0.0
+        }
+    }
+
+    /// Sets the presynaptic input (optional) that releases the specified amount of dopamine when it spikes.
+    pub fn set_source(&mut self, source: Option<PresynapticInput>, release: f64) {
+        self.source = source;
+        self.release = release;
+    }
+
+    /// Returns the model file record of the dopamine signal.
+    pub fn record(&self, refs: &mut ModelRefs) -> Result<DopamineRecord, String> {
+        Ok(DopamineRecord {
+            td: self.td,
+            tonic: self.tonic,
+            source: self.source.as_ref().map(|source| refs.input(source)).transpose()?,
+            release: self.release
+        })
+    }
+
+    /// Returns the dynamic state of the dopamine signal (the concentration excluding the tonic concentration).
+    pub fn state(&self) -> f64 {
+        self.d
+    }
+
+    /// Restores the dynamic state of the dopamine signal.
+    pub fn restore(&mut self, d: f64) {
+        self.d = d;
+    }
+
+    /// Delivers the specified amount of reward (phasic dopamine).
+    pub fn reward(&mut self, amount: f64) {
+        self.d += amount;
+    }
+
+    /// Returns the current dopamine concentration, including the tonic concentration.
+    pub fn level(&self) -> f64 {
+        self.d + self.tonic
+    }
+
+    /// Processes and returns the next dopamine concentration after the specified time step (ms).
+    pub fn process(&mut self, time: f64) -> f64 {
+        self.d *= (-time / self.td).exp();
+
+        if let Some(source) = &self.source {
+            if source.spike() {
+                self.d += self.release;
+            }
+        }
+
+        self.level()
+    }
+}
+
+/// Contains data for reward-modulated STDP with an eligibility trace (Izhikevich, 2007).
 #[derive(Clone, Debug, PartialEq)]
+pub struct RewardModulatedPlasticity {
+    /// STDP data used to build the eligibility trace.
+    stdp: SpikeTimingDependentPlasticity,
+    /// Eligibility trace.
+    c: f64,
+    /// Eligibility trace decay time.
+    tc: f64,
+    /// The dopamine signal that converts the eligibility trace into weight changes.
+    dopamine: Rc<RefCell<DopamineSignal>>
+}
+
+// RewardModulatedPlasticity functions
+impl RewardModulatedPlasticity {
+    /// Creates new reward-modulated STDP data with the specified parameters.
+    pub fn new(stdp: SpikeTimingDependentPlasticity, tc: f64, dopamine: Rc<RefCell<DopamineSignal>>) -> Self {
+        Self {
+            stdp,
+            c: 0.0,
+            tc,
+            dopamine
+        }
+    }
+
+    /// Returns the eligibility trace.
+    pub fn c(&self) -> f64 {
+        self.c
+    }
+
+    /// Processes the eligibility trace based on the specified presynaptic and postsynaptic spikes, current synaptic
+    /// weight and time step (ms) and returns the new synaptic weight.
+    pub fn learn(&mut self, pre: bool, post: bool, w: f64, time: f64) -> f64 {
+        self.c *= (-time / self.tc).exp();
+        self.c += self.stdp.dw(pre, post, time);
+
+        let w = w + time * self.c * self.dopamine.borrow().level();
+
+        w.max(self.stdp.w_min).min(self.stdp.w_max)
+    }
+}
+
+/// Contains a list of long-term plasticity types.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LongTermPlasticity {
+    /// Spike-timing-dependent plasticity.
+    Stdp(SpikeTimingDependentPlasticity),
+    /// Reward-modulated spike-timing-dependent plasticity.
+    RewardModulated(RewardModulatedPlasticity)
+}
+
+// LongTermPlasticity functions
+impl LongTermPlasticity {
+    /// Processes the long-term plasticity based on the specified presynaptic and postsynaptic spikes, current
+    /// synaptic weight and time step (ms) and returns the new synaptic weight.
+    pub fn learn(&mut self, pre: bool, post: bool, w: f64, time: f64) -> f64 {
+        match self {
+            LongTermPlasticity::Stdp(p) => p.learn(pre, post, w, time),
+            LongTermPlasticity::RewardModulated(p) => p.learn(pre, post, w, time)
+        }
+    }
+
+    /// Returns the dynamic state of the long-term plasticity.
+    pub fn state(&self) -> LtpState {
+        match self {
+            LongTermPlasticity::Stdp(p) => LtpState::Stdp(p.clone()),
+            LongTermPlasticity::RewardModulated(p) => LtpState::RewardModulated(p.stdp.clone(), p.c)
+        }
+    }
+
+    /// Restores the dynamic state of the long-term plasticity. A state of a different type of plasticity is ignored.
+    pub fn restore(&mut self, state: &LtpState) {
+        match (self, state) {
+            (LongTermPlasticity::Stdp(p), LtpState::Stdp(stdp)) => *p = stdp.clone(),
+            (LongTermPlasticity::RewardModulated(p), LtpState::RewardModulated(stdp, c)) => {
+                p.stdp = stdp.clone();
+                p.c = *c;
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the model file record of the long-term plasticity. The eligibility trace is not recorded.
+    pub fn record(&self, refs: &mut ModelRefs) -> LtpRecord {
+        match self {
+            LongTermPlasticity::Stdp(p) => LtpRecord::Stdp(p.clone()),
+            LongTermPlasticity::RewardModulated(p) => LtpRecord::RewardModulated(p.stdp.clone(), p.tc, refs.dopamine(&p.dopamine))
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains a list of modulation modes.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum ModulationMode {
+    /// The target variable is scaled by (1 + gain * level). State variables grow at the rate (gain * level) per ms.
+    Scale,
+    /// The target variable is shifted by (gain * level). State variables are shifted by (gain * level) per ms.
+    Shift
+}
+
+/// Contains data for how a modulatory synapse modulates its target variable.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Modulation {
+    /// The modulation mode.
+    mode: ModulationMode,
+    /// The modulation gain.
+    gain: f64,
+    /// The lower bound of the modulated variable.
+    #[serde(with = "crate::model::extended_f64")]
+    min: f64,
+    /// The upper bound of the modulated variable.
+    #[serde(with = "crate::model::extended_f64")]
+    max: f64
+}
+
+// Default for Modulation
+impl Default for Modulation {
+    fn default() -> Self {
+        Self {
+            mode: ModulationMode::Scale,
+            gain: 1.0,
+            min: f64::NEG_INFINITY,
+            max: f64::INFINITY
+        }
+    }
+}
+
+// Modulation functions
+impl Modulation {
+    /// Creates new modulation data with the specified parameters.
+    pub fn new(mode: ModulationMode, gain: f64, min: f64, max: f64) -> Self {
+        Self {
+            mode,
+            gain,
+            min,
+            max
+        }
+    }
+
+    /// Returns the specified base value modulated by the specified modulation level and clamped to the bounds.
+    pub fn apply(&self, base: f64, level: f64) -> f64 {
+        let value = match self.mode {
+            ModulationMode::Scale => base * (1.0 + self.gain * level),
+            ModulationMode::Shift => base + self.gain * level
+        };
+
+        value.max(self.min).min(self.max)
+    }
+
+    /// Returns the specified state variable value driven by the specified modulation level over the specified time
+    /// step (ms) and clamped to the bounds. The drive is a rate per ms, so the result does not depend on the time step.
+    pub fn drive(&self, value: f64, level: f64, time: f64) -> f64 {
+        let value = match self.mode {
+            ModulationMode::Scale => value * (self.gain * level * time).exp(),
+            ModulationMode::Shift => value + self.gain * level * time
+        };
+
+        value.max(self.min).min(self.max)
+    }
+}
+
+/// Contains data for a neurite parameter modulated by neural modulatory synapses.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ModulatedParameter {
+    /// The modulated parameter.
+    pub var: NeuriteModVar,
+    /// Value of the parameter before it was first modulated.
+    pub base: f64,
+    /// Whether the parameter has already been modulated in the current time step.
+    pub stacked: bool
+}
+
+/// Returns the specified base value of a synaptic variable modulated by the specified (optional) synaptic modulatory
+/// synapse.
+fn modulated(mod_syn: &Option<Rc<RefCell<SynapticModulatorySynapse>>>, var: SynapticModVar, base: f64) -> f64 {
+    match mod_syn {
+        Some(m) => m.borrow().modulate(var, base),
+        None => base
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a pulse electrode.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct PulseElectrode {
     /// Active electrode flag.
     active: bool,
@@ -201,6 +915,11 @@ impl Electrode for PulseElectrode {
         self.v
     }
 
+    /// Sets the voltage input of the electrode.
+    fn set_voltage(&mut self, v: f64) {
+        self.v = v;
+    }
+
     /// Returns the duration of the electrode's pulse.
     fn duration(&self) -> f64 {
         self.d
@@ -211,19 +930,19 @@ impl Electrode for PulseElectrode {
         self.y
     }
 
-    /// Processes and returns the next output of the electrode.
-    fn process(&mut self) -> f64 {
+    /// Processes and returns the next output of the electrode after the specified time step (ms).
+    fn process(&mut self, time: f64) -> f64 {
         if self.active {
             if self.a <= self.d {
-                self.a += TIME_INC;
+                self.a += time;
                 self.y = self.v;
-                return self.v;
+                self.v
             }
             else {
                 self.a = 0.0;
                 self.active = false;
                 self.y = 0.0;
-                return 0.0;
+                0.0
             }
         }
         else {
@@ -232,6 +951,8 @@ impl Electrode for PulseElectrode {
             0.0
         }
     }
+
+    electrode_functions!(Pulse; active: false, a: 0.0, y: 0.0);
 }
 
 // PulseElectrode functions
@@ -250,25 +971,19 @@ impl PulseElectrode {
 
 // -------------------------------------------------------------------------------------------------
 
-/// Contains data for a pulsating electrode.
-#[derive(Clone, Debug, PartialEq)]
-pub struct PulsatingElectrode {
+/// Contains data for a command electrode (an electrode whose output follows its voltage while it is on).
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct CommandElectrode {
     /// Active electrode flag.
     active: bool,
-    /// The length of time the electrode has been active.
-    a: f64,
-    /// The electrode's voltage.
+    /// The electrode's voltage (the command).
     v: f64,
-    /// The duration of the pulse.
-    d: f64,
-    /// The time between pulses.
-    t: f64,
     /// Current output of the electrode.
     y: f64
 }
 
 // Electrode functions
-impl Electrode for PulsatingElectrode {
+impl Electrode for CommandElectrode {
     /// Turns on (activates) the electrode.
     fn on(&mut self) {
         self.active = true;
@@ -284,9 +999,15 @@ impl Electrode for PulsatingElectrode {
         self.v
     }
 
-    /// Returns the duration of the electrode's pulse.
+    /// Sets the voltage input of the electrode.
+    fn set_voltage(&mut self, v: f64) {
+        self.v = v;
+    }
+
+    /// Returns the duration of the electrode's pulse (infinite, as the electrode holds its voltage until it is turned
+    /// off).
     fn duration(&self) -> f64 {
-        self.d
+        f64::INFINITY
     }
 
     /// Returns the current output of the electrode.
@@ -294,18 +1015,90 @@ impl Electrode for PulsatingElectrode {
         self.y
     }
 
-    /// Processes and returns the next output of the electrode.
-    fn process(&mut self) -> f64 {
-        if self.active {
-            self.a += TIME_INC;
+    /// Processes and returns the next output of the electrode after the specified time step (ms).
+    fn process(&mut self, _time: f64) -> f64 {
+        self.y = if self.active { self.v } else { 0.0 };
+        self.y
+    }
+
+    electrode_functions!(Command; active: false, y: 0.0);
+}
+
+// CommandElectrode functions
+impl CommandElectrode {
+    /// Creates a new command electrode with the specified voltage.
+    pub fn new(v: f64) -> Self {
+        Self {
+            active: false,
+            v,
+            y: 0.0
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a pulsating electrode.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct PulsatingElectrode {
+    /// Active electrode flag.
+    active: bool,
+    /// The length of time the electrode has been active.
+    a: f64,
+    /// The electrode's voltage.
+    v: f64,
+    /// The duration of the pulse.
+    d: f64,
+    /// The time between pulses.
+    t: f64,
+    /// Current output of the electrode.
+    y: f64
+}
+
+// Electrode functions
+impl Electrode for PulsatingElectrode {
+    /// Turns on (activates) the electrode.
+    fn on(&mut self) {
+        self.active = true;
+    }
+
+    /// Turns off (deactivates) the electrode.
+    fn off(&mut self) {
+        self.active = false;
+    }
+
+    /// Returns the voltage input of the electrode.
+    fn voltage(&self) -> f64 {
+        self.v
+    }
+
+    /// Sets the voltage input of the electrode.
+    fn set_voltage(&mut self, v: f64) {
+        self.v = v;
+    }
+
+    /// Returns the duration of the electrode's pulse.
+    fn duration(&self) -> f64 {
+        self.d
+    }
+
+    /// Returns the current output of the electrode.
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    /// Processes and returns the next output of the electrode after the specified time step (ms).
+    fn process(&mut self, time: f64) -> f64 {
+        if self.active {
+            self.a += time;
 
             if self.a % (self.d + self.t) <= self.d {
                 self.y = self.v;
-                return self.v;
+                self.v
             }
             else {
                 self.y = 0.0;
-                return 0.0;
+                0.0
             }
         }
         else {
@@ -314,6 +1107,8 @@ impl Electrode for PulsatingElectrode {
             0.0
         }
     }
+
+    electrode_functions!(Pulsating; active: false, a: 0.0, y: 0.0);
 }
 
 // PulsatingElectrode functions
@@ -334,7 +1129,7 @@ impl PulsatingElectrode {
 // -------------------------------------------------------------------------------------------------
 
 /// Contains data for a sinusoidal electrode.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct SinusoidalElectrode {
     /// Active electrode flag.
     active: bool,
@@ -367,6 +1162,11 @@ impl Electrode for SinusoidalElectrode {
         self.v
     }
 
+    /// Sets the voltage input of the electrode.
+    fn set_voltage(&mut self, v: f64) {
+        self.v = v;
+    }
+
     /// Returns the duration of the electrode's pulse.
     fn duration(&self) -> f64 {
         self.d
@@ -377,13 +1177,13 @@ impl Electrode for SinusoidalElectrode {
         self.y
     }
 
-    /// Processes and returns the next output of the electrode.
-    fn process(&mut self) -> f64 {
+    /// Processes and returns the next output of the electrode after the specified time step (ms).
+    fn process(&mut self, time: f64) -> f64 {
         if self.active {
-            self.a += TIME_INC;
+            self.a += time;
 
             self.y = self.v * ((2.0 * PI * (1.0 / self.d) * (self.a - self.p)).sin() * 0.5 + 0.5);
-            return self.y;
+            self.y
         }
         else {
             self.a = 0.0;
@@ -391,6 +1191,8 @@ impl Electrode for SinusoidalElectrode {
             0.0
         }
     }
+
+    electrode_functions!(Sinusoidal; active: false, a: 0.0, y: 0.0);
 }
 
 // SinusoidalElectrode functions
@@ -410,8 +1212,47 @@ impl SinusoidalElectrode {
 
 // -------------------------------------------------------------------------------------------------
 
+/// Contains a list of spike rate functions (Hz) over time (ms).
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum RateFunction {
+    /// Constant rate.
+    Constant(f64),
+    /// Sinusoidal rate with the specified mean, amplitude, period (ms) and phase shift (ms).
+    Sinusoidal(f64, f64, f64, f64),
+    /// Piecewise constant rate from (start time, rate) pairs in order of start time. The rate is 0 before the first
+    /// start time.
+    Piecewise(Vec<(f64, f64)>)
+}
+
+// RateFunction functions
+impl RateFunction {
+    /// Returns the (non-negative) rate (Hz) at the specified time (ms).
+    pub fn rate(&self, t: f64) -> f64 {
+        let rate = match self {
+            RateFunction::Constant(f) => *f,
+            RateFunction::Sinusoidal(mean, amp, period, phase) => mean + amp * (2.0 * PI * (t - phase) / period).sin(),
+            RateFunction::Piecewise(steps) => {
+                steps.iter().take_while(|(start, _)| *start <= t).last().map_or(0.0, |(_, f)| *f)
+            }
+        };
+
+        rate.max(0.0)
+    }
+}
+
+/// Contains a list of refractory variants of a poisson process.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Refractory {
+    /// No refractoriness (exponential inter-spike intervals).
+    None,
+    /// Dead time (ms) after each spike. The rate outside of the dead time is raised so the mean rate is unchanged.
+    DeadTime(f64),
+    /// Gamma distributed inter-spike intervals of the specified (integer) order with an unchanged mean rate.
+    Gamma(u32)
+}
+
 /// Contains data for a poisson electrode.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct PoissonElectrode {
     /// Active electrode flag.
     active: bool,
@@ -421,18 +1262,33 @@ pub struct PoissonElectrode {
     v: f64,
     /// The duration of the pulse.
     d: f64,
-    /// The average pulse frequency.
-    f: f64,
-    /// The time length between pulses.
-    t: f64,
+    /// The pulse rate function.
+    f: RateFunction,
+    /// The refractory variant.
+    refractory: Refractory,
+    /// The integrated rate since the last pulse.
+    s: f64,
+    /// The integrated rate at which the next pulse starts.
+    th: f64,
+    /// The remaining dead time.
+    dead: f64,
+    /// The remaining pulse time.
+    p: f64,
     /// Current output of the electrode.
-    y: f64
+    y: f64,
+    /// Random number generator.
+    rng: SimRng
 }
 
 // Electrode functions
 impl Electrode for PoissonElectrode {
-    /// Turns on (activates) the electrode.
+    /// Turns on (activates) the electrode. The integrated rate is only reset if the electrode was off.
     fn on(&mut self) {
+        if !self.active {
+            self.s = 0.0;
+            self.th = self.interval();
+        }
+
         self.active = true;
     }
 
@@ -446,6 +1302,11 @@ impl Electrode for PoissonElectrode {
         self.v
     }
 
+    /// Sets the voltage input of the electrode.
+    fn set_voltage(&mut self, v: f64) {
+        self.v = v;
+    }
+
     /// Returns the duration of the electrode's pulse.
     fn duration(&self) -> f64 {
         self.d
@@ -456,184 +1317,1438 @@ impl Electrode for PoissonElectrode {
         self.y
     }
 
-    /// Processes and returns the next output of the electrode.
-    fn process(&mut self) -> f64 {
+    /// Processes and returns the next output of the electrode after the specified time step (ms). Pulses start at
+    /// the events of a (possibly inhomogeneous) poisson process, found by integrating the rate up to a random
+    /// threshold drawn from the unit mean inter-spike interval distribution (time rescaling).
+    fn process(&mut self, time: f64) -> f64 {
         if self.active {
-            let prev_a: f64 = self.a;
-            self.a += TIME_INC;
+            self.a += time;
+            self.p -= time;
+
+            if self.dead > 0.0 {
+                self.dead -= time;
+            }
+            else {
+                // Rate per ms
+                let mut r = self.f.rate(self.a) / 1000.0;
 
-            if prev_a == 0.0 || self.a % 1000.0 < prev_a {
-                let r: f64 = random();
-                let mut k: i128 = 0;
-                let mut p: f64 = (-self.f).exp();
+                if let Refractory::DeadTime(t) = self.refractory {
+                    r = if r * t < 1.0 { r / (1.0 - r * t) } else { f64::INFINITY };
+                }
 
-                while r > p {
-                    k += 1;
-                    let mut fact: i128 = 1;
+                self.s += r * time;
 
-                    for i in 1..=k {
-                        fact *= i;
-                    }
+                if self.s >= self.th {
+                    self.s = 0.0;
+                    self.th = self.interval();
+                    self.p = self.d;
 
-                    p += (self.f.pow(k) * (-self.f).exp()) / fact;
+                    if let Refractory::DeadTime(t) = self.refractory {
+                        self.dead = t;
+                    }
                 }
-
-                self.t = (1000.0 / k as f64) - 1.0;
             }
 
-            if self.a % (self.d + self.t) <= self.d {
-                self.y = self.v;
-                return self.v;
-            }
-            else {
-                self.y = 0.0;
-                return 0.0;
-            }
+            self.y = if self.p > 0.0 { self.v } else { 0.0 };
+            self.y
         }
         else {
             self.a = 0.0;
+            self.s = 0.0;
+            self.dead = 0.0;
+            self.p = 0.0;
             self.y = 0.0;
             0.0
         }
     }
+
+    /// Seeds the electrode's random number generator.
+    fn seed(&mut self, rng: SimRng) {
+        self.rng = rng;
+        self.th = self.interval();
+    }
+
+    electrode_functions!(Poisson; active: false, a: 0.0, s: 0.0, th: 0.0, dead: 0.0, p: 0.0, y: 0.0, rng: SimRng::seed_from_u64(0));
 }
 
 // PoissonElectrode functions
 impl PoissonElectrode {
-    /// Creates a new poisson electrode with the specified parameters.
+    /// Creates a new poisson electrode with the specified voltage and constant average pulse frequency (Hz).
     pub fn new(v: f64, f: f64) -> Self {
-        Self {
+        Self::inhomogeneous(v, RateFunction::Constant(f))
+    }
+
+    /// Creates a new poisson electrode with the specified voltage and pulse rate function.
+    pub fn inhomogeneous(v: f64, f: RateFunction) -> Self {
+        let mut electrode = Self {
             active: false,
             a: 0.0,
             v,
             d: 1.0,
             f,
-            t: 0.0,
-            y: 0.0
+            refractory: Refractory::None,
+            s: 0.0,
+            th: 0.0,
+            dead: 0.0,
+            p: 0.0,
+            y: 0.0,
+            rng: SimRng::from_entropy()
+        };
+
+        electrode.th = electrode.interval();
+        electrode
+    }
+
+    /// Sets the refractory variant.
+    pub fn set_refractory(&mut self, refractory: Refractory) {
+        self.refractory = refractory;
+        self.th = self.interval();
+    }
+
+    /// Sets the duration of the pulse (ms).
+    pub fn set_duration(&mut self, d: f64) {
+        self.d = d;
+    }
+
+    /// Returns a random inter-spike interval of a unit rate process.
+    fn interval(&mut self) -> f64 {
+        match self.refractory {
+            Refractory::Gamma(k) => {
+                let k = k.max(1);
+                (0..k).map(|_| -(1.0 - self.rng.gen::<f64>()).ln()).sum::<f64>() / k as f64
+            }
+            _ => -(1.0 - self.rng.gen::<f64>()).ln()
         }
     }
 }
 
 // -------------------------------------------------------------------------------------------------
 
-/// Contains data for an excitatory synapse.
-#[derive(Clone, Debug, PartialEq)]
-pub struct ExcitatorySynapse {
-    /// The synaptic ID.
-    id: SynapticID,
-    /// The type of synapse.
-    syn_type: SynapseType,
-    /// The presynaptic input.
-    x_pre: PresynapticInput,
-    /// The postsynaptic neurite.
-    x_post: Rc<RefCell<Neurite>>,
-    /// A synaptic modulatory synapse (optional) connected to this synapse.
-    mod_syn: Option<Rc<RefCell<SynapticModulatorySynapse>>>,
-    /// The input conductance of the synapse (total input).
-    x: f64,
-    /// Conductance decay time.
-    tx: f64,
-    /// Short-term plasticity data.
-    stp: ShortTermPlasticity,
-    /// Synaptic weight.
-    w: f64,
-    /// Maximal synaptic input conductance (maximal input).
-    x_max: f64
+/// Contains data for a gaussian white noise electrode.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct WhiteNoiseElectrode {
+    /// Active electrode flag.
+    active: bool,
+    /// The length of time the electrode has been active.
+    a: f64,
+    /// The electrode's mean voltage.
+    v: f64,
+    /// The noise intensity (standard deviation of the output integrated over 1 ms).
+    sigma: f64,
+    /// Current output of the electrode.
+    y: f64,
+    /// Random number generator.
+    rng: SimRng
 }
 
-// Synapse functions for ExcitatorySynapse
-impl Synapse for ExcitatorySynapse {
-    /// Returns the synaptic ID.
-    fn syn_id(&self) -> SynapticID {
-        self.id
+// Electrode functions
+impl Electrode for WhiteNoiseElectrode {
+    /// Turns on (activates) the electrode.
+    fn on(&mut self) {
+        self.active = true;
     }
 
-    /// Returns the synaptic type.
-    fn syn_type(&self) -> SynapseType {
-        self.syn_type
+    /// Turns off (deactivates) the electrode.
+    fn off(&mut self) {
+        self.active = false;
     }
 
-    /// Returns the presynaptic input.
-    fn x_pre(&self) -> PresynapticInput {
-        self.x_pre.clone()
+    /// Returns the voltage input of the electrode.
+    fn voltage(&self) -> f64 {
+        self.v
     }
 
-    /// Returns the current total synaptic input.
-    fn input(&self) -> f64 {
-        self.x
+    /// Sets the voltage input of the electrode.
+    fn set_voltage(&mut self, v: f64) {
+        self.v = v;
     }
 
-    /// Processes and returns the next total input of the synapse.
-    fn process(&mut self) -> f64 {
-        self.x += -self.x / self.tx;
+    /// Returns the duration of the electrode's pulse (white noise has no correlation time).
+    fn duration(&self) -> f64 {
+        0.0
+    }
 
-        match self.x_pre.clone() {
-            PresynapticInput::Electrode(e) => {
-                self.stp.learn(e.borrow().output() > 0.0);
-            }
-            PresynapticInput::Sensor(s) => {
-                self.stp.learn(s.borrow().output() > 0.0);
-            }
-            PresynapticInput::Neurite(n) => {
-                self.stp.learn(n.borrow().y > 0.0);
-            }
-        }
+    /// Returns the current output of the electrode.
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    /// Processes and returns the next output of the electrode after the specified time step (ms). The noise is scaled
+    /// by 1 / sqrt(time step) so its integrated effect does not depend on the time step.
+    fn process(&mut self, time: f64) -> f64 {
+        if self.active {
+            self.a += time;
 
-        self.x += self.x_max * self.stp.y * self.w;
+            let n: f64 = self.rng.sample(StandardNormal);
+            self.y = self.v + self.sigma * n / time.sqrt();
+            self.y
+        }
+        else {
+            self.a = 0.0;
+            self.y = 0.0;
+            0.0
+        }
+    }
 
-        self.x
+    /// Seeds the electrode's random number generator.
+    fn seed(&mut self, rng: SimRng) {
+        self.rng = rng;
     }
+
+    electrode_functions!(WhiteNoise; active: false, a: 0.0, y: 0.0, rng: SimRng::seed_from_u64(0));
 }
 
-// ExcitatorySynapse functions
-impl ExcitatorySynapse {
-    /// Creates a new excitatory synapse with the specified parameters.
-    pub fn new(x_pre: PresynapticInput, x_post: Rc<RefCell<Neurite>>, stp: ShortTermPlasticity, tx: f64, x_max: f64) -> Self {
+// WhiteNoiseElectrode functions
+impl WhiteNoiseElectrode {
+    /// Creates a new white noise electrode with the specified parameters.
+    pub fn new(v: f64, sigma: f64) -> Self {
         Self {
-            id: SynapticID::new(),
-            syn_type: SynapseType::Excitatory,
-            x_pre,
-            x_post,
-            mod_syn: None,
-            x: 0.0,
-            tx,
-            stp,
-            w: 0.0,
-            x_max
+            active: false,
+            a: 0.0,
+            v,
+            sigma,
+            y: 0.0,
+            rng: SimRng::from_entropy()
         }
     }
 }
 
 // -------------------------------------------------------------------------------------------------
 
-/// Contains data for an inhibitory synapse.
-#[derive(Clone, Debug, PartialEq)]
-pub struct InhibitorySynapse {
-    /// The synaptic ID.
-    id: SynapticID,
-    /// The type of synapse.
-    syn_type: SynapseType,
-    /// The presynaptic input.
-    x_pre: PresynapticInput,
-    /// The postsynaptic neurite.
-    x_post: Rc<RefCell<Neurite>>,
-    /// A synaptic modulatory synapse (optional) connected to this synapse.
-    mod_syn: Option<Rc<RefCell<SynapticModulatorySynapse>>>,
-    /// The input conductance of the synapse (total input).
-    x: f64,
-    /// Conductance decay time.
-    tx: f64,
-    /// Short-term plasticity data.
-    stp: ShortTermPlasticity,
-    /// Synaptic weight.
-    w: f64,
-    /// Maximal synaptic input conductance (maximal input).
-    x_max: f64
+/// Contains data for an Ornstein-Uhlenbeck (colored noise) electrode.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct OrnsteinUhlenbeckElectrode {
+    /// Active electrode flag.
+    active: bool,
+    /// The length of time the electrode has been active.
+    a: f64,
+    /// The electrode's mean voltage.
+    v: f64,
+    /// The stationary standard deviation of the output.
+    sigma: f64,
+    /// The correlation time of the noise.
+    tau: f64,
+    /// Current output of the electrode.
+    y: f64,
+    /// Random number generator.
+    rng: SimRng
+}
+
+// Electrode functions
+impl Electrode for OrnsteinUhlenbeckElectrode {
+    /// Turns on (activates) the electrode.
+    fn on(&mut self) {
+        self.active = true;
+    }
+
+    /// Turns off (deactivates) the electrode.
+    fn off(&mut self) {
+        self.active = false;
+    }
+
+    /// Returns the voltage input of the electrode.
+    fn voltage(&self) -> f64 {
+        self.v
+    }
+
+    /// Sets the voltage input of the electrode.
+    fn set_voltage(&mut self, v: f64) {
+        self.v = v;
+    }
+
+    /// Returns the duration of the electrode's pulse (the correlation time of the noise).
+    fn duration(&self) -> f64 {
+        self.tau
+    }
+
+    /// Returns the current output of the electrode.
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    /// Processes and returns the next output of the electrode after the specified time step (ms). The process is
+    /// updated with its exact solution, so it is stable for any time step.
+    fn process(&mut self, time: f64) -> f64 {
+        if self.active {
+            if self.a == 0.0 {
+                self.y = self.v;
+            }
+
+            self.a += time;
+
+            let decay = (-time / self.tau).exp();
+            let n: f64 = self.rng.sample(StandardNormal);
+
+            self.y = self.v + (self.y - self.v) * decay + self.sigma * (1.0 - decay * decay).sqrt() * n;
+            self.y
+        }
+        else {
+            self.a = 0.0;
+            self.y = 0.0;
+            0.0
+        }
+    }
+
+    /// Seeds the electrode's random number generator.
+    fn seed(&mut self, rng: SimRng) {
+        self.rng = rng;
+    }
+
+    electrode_functions!(OrnsteinUhlenbeck; active: false, a: 0.0, y: 0.0, rng: SimRng::seed_from_u64(0));
+}
+
+// OrnsteinUhlenbeckElectrode functions
+impl OrnsteinUhlenbeckElectrode {
+    /// Creates a new Ornstein-Uhlenbeck electrode with the specified parameters.
+    pub fn new(v: f64, sigma: f64, tau: f64) -> Self {
+        Self {
+            active: false,
+            a: 0.0,
+            v,
+            sigma,
+            tau,
+            y: 0.0,
+            rng: SimRng::from_entropy()
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a shot noise electrode.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ShotNoiseElectrode {
+    /// Active electrode flag.
+    active: bool,
+    /// The length of time the electrode has been active.
+    a: f64,
+    /// The electrode's voltage increment per shot.
+    v: f64,
+    /// The average shot frequency (Hz).
+    f: f64,
+    /// The decay time of each shot.
+    tau: f64,
+    /// Current output of the electrode.
+    y: f64,
+    /// Random number generator.
+    rng: SimRng
+}
+
+// Electrode functions
+impl Electrode for ShotNoiseElectrode {
+    /// Turns on (activates) the electrode.
+    fn on(&mut self) {
+        self.active = true;
+    }
+
+    /// Turns off (deactivates) the electrode.
+    fn off(&mut self) {
+        self.active = false;
+    }
+
+    /// Returns the voltage input of the electrode.
+    fn voltage(&self) -> f64 {
+        self.v
+    }
+
+    /// Sets the voltage input of the electrode.
+    fn set_voltage(&mut self, v: f64) {
+        self.v = v;
+    }
+
+    /// Returns the duration of the electrode's pulse (the decay time of each shot).
+    fn duration(&self) -> f64 {
+        self.tau
+    }
+
+    /// Returns the current output of the electrode.
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    /// Processes and returns the next output of the electrode after the specified time step (ms). The number of shots
+    /// in each time step is poisson distributed.
+    fn process(&mut self, time: f64) -> f64 {
+        if self.active {
+            self.a += time;
+            self.y *= (-time / self.tau).exp();
+
+            // Number of shots (Knuth's method)
+            let l = (-self.f * time / 1000.0).exp();
+            let mut p: f64 = self.rng.gen();
+
+            while p > l {
+                self.y += self.v;
+                p *= self.rng.gen::<f64>();
+            }
+
+            self.y
+        }
+        else {
+            self.a = 0.0;
+            self.y = 0.0;
+            0.0
+        }
+    }
+
+    /// Seeds the electrode's random number generator.
+    fn seed(&mut self, rng: SimRng) {
+        self.rng = rng;
+    }
+
+    electrode_functions!(ShotNoise; active: false, a: 0.0, y: 0.0, rng: SimRng::seed_from_u64(0));
+}
+
+// ShotNoiseElectrode functions
+impl ShotNoiseElectrode {
+    /// Creates a new shot noise electrode with the specified parameters.
+    pub fn new(v: f64, f: f64, tau: f64) -> Self {
+        Self {
+            active: false,
+            a: 0.0,
+            v,
+            f,
+            tau,
+            y: 0.0,
+            rng: SimRng::from_entropy()
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for an arbitrary waveform electrode.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct WaveformElectrode {
+    /// Active electrode flag.
+    active: bool,
+    /// The length of time the electrode has been active.
+    a: f64,
+    /// The waveform samples.
+    samples: Vec<f64>,
+    /// The time between samples (ms).
+    si: f64,
+    /// Set if the waveform loops.
+    looping: bool,
+    /// The waveform scale.
+    scale: f64,
+    /// The waveform offset.
+    offset: f64,
+    /// Current output of the electrode.
+    y: f64
+}
+
+// Electrode functions
+impl Electrode for WaveformElectrode {
+    /// Turns on (activates) the electrode.
+    fn on(&mut self) {
+        self.active = true;
+    }
+
+    /// Turns off (deactivates) the electrode.
+    fn off(&mut self) {
+        self.active = false;
+    }
+
+    /// Returns the voltage input of the electrode (the waveform scale).
+    fn voltage(&self) -> f64 {
+        self.scale
+    }
+
+    /// Sets the voltage input of the electrode (the waveform scale).
+    fn set_voltage(&mut self, v: f64) {
+        self.scale = v;
+    }
+
+    /// Returns the duration of the electrode's pulse (the length of the waveform).
+    fn duration(&self) -> f64 {
+        self.samples.len() as f64 * self.si
+    }
+
+    /// Returns the current output of the electrode.
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    /// Processes and returns the next output of the electrode after the specified time step (ms). The waveform is
+    /// linearly interpolated at the time the electrode has been active, so it plays back at its own sample rate for
+    /// any time step.
+    fn process(&mut self, time: f64) -> f64 {
+        if self.active && !self.samples.is_empty() {
+            let length = self.duration();
+            let mut t = self.a;
+
+            if t >= length {
+                if self.looping {
+                    t %= length;
+                }
+                else {
+                    self.a = 0.0;
+                    self.active = false;
+                    self.y = 0.0;
+                    return 0.0;
+                }
+            }
+
+            self.a += time;
+            self.y = self.scale * interpolate(&self.samples, self.si, t, self.looping) + self.offset;
+            self.y
+        }
+        else {
+            self.a = 0.0;
+            self.y = 0.0;
+            0.0
+        }
+    }
+
+    electrode_functions!(Waveform; active: false, a: 0.0, y: 0.0);
+}
+
+// WaveformElectrode functions
+impl WaveformElectrode {
+    /// Creates a new waveform electrode with the specified samples and time between samples (ms). If the time between
+    /// samples is not positive and finite, this returns an error message.
+    pub fn new(samples: Vec<f64>, si: f64) -> Result<Self, &'static str> {
+        check_sample_interval(si)?;
+
+        Ok(Self {
+            active: false,
+            a: 0.0,
+            samples,
+            si,
+            looping: false,
+            scale: 1.0,
+            offset: 0.0,
+            y: 0.0
+        })
+    }
+
+    /// Creates a new waveform electrode from the specified (zero based) column of a CSV file and time between samples
+    /// (ms). Rows that cannot be read as a number (such as a header) are skipped.
+    pub fn from_csv(path: &str, column: usize, si: f64) -> Result<Self, String> {
+        Ok(Self::new(read_csv_column(path, column)?, si)?)
+    }
+
+    /// Creates a new waveform electrode from the first channel of a WAV file. Integer samples are normalized to -1..1.
+    pub fn from_wav(path: &str) -> Result<Self, String> {
+        let mut reader = hound::WavReader::open(path).map_err(|e| format!("Cannot read WAV file {}: {}", path, e))?;
+        let spec = reader.spec();
+        let channels = spec.channels.max(1) as usize;
+
+        let samples: Vec<f64> = match spec.sample_format {
+            hound::SampleFormat::Float => {
+                reader.samples::<f32>()
+                    .step_by(channels)
+                    .map(|s| s.map(|s| s as f64))
+                    .collect::<Result<_, _>>()
+            }
+            hound::SampleFormat::Int => {
+                let max = (1i64 << (spec.bits_per_sample - 1)) as f64;
+
+                reader.samples::<i32>()
+                    .step_by(channels)
+                    .map(|s| s.map(|s| s as f64 / max))
+                    .collect::<Result<_, _>>()
+            }
+        }.map_err(|e| format!("Cannot read WAV file {}: {}", path, e))?;
+
+        Self::new(samples, 1000.0 / spec.sample_rate as f64).map_err(|e| format!("Cannot read WAV file {}: {}", path, e))
+    }
+
+    /// Returns an error message if the time between samples is not positive and finite (such as in a model file).
+    pub fn check(&self) -> Result<(), &'static str> {
+        check_sample_interval(self.si)
+    }
+
+    /// Sets if the waveform loops.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Sets the waveform scale.
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = scale;
+    }
+
+    /// Sets the waveform offset.
+    pub fn set_offset(&mut self, offset: f64) {
+        self.offset = offset;
+    }
+}
+
+/// Reads the specified (zero based) column of a CSV file. Rows that cannot be read as a number (such as a header) are
+/// skipped.
+pub fn read_csv_column(path: &str, column: usize) -> Result<Vec<f64>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Cannot read CSV file {}: {}", path, e))?;
+
+    let samples: Vec<f64> = text.lines()
+        .filter_map(|line| line.split(',').nth(column))
+        .filter_map(|value| value.trim().parse::<f64>().ok())
+        .collect();
+
+    if samples.is_empty() {
+        return Err(format!("CSV file {} has no numeric values in column {}.", path, column));
+    }
+
+    Ok(samples)
+}
+
+/// Returns an error message if the specified time between samples (ms) is not positive and finite.
+fn check_sample_interval(si: f64) -> Result<(), &'static str> {
+    if si > 0.0 && si.is_finite() {
+        Ok(())
+    }
+    else {
+        Err("Time between samples must be positive and finite.")
+    }
+}
+
+/// Returns the specified samples, with the specified time between samples (ms), linearly interpolated at the specified
+/// time (ms) within their length. The last sample is interpolated toward the first if the samples loop.
+fn interpolate(samples: &[f64], si: f64, t: f64, looping: bool) -> f64 {
+    let pos = t / si;
+    // Rounding can put a time just below the length on the sample past the end
+    let i = (pos.floor() as usize).min(samples.len() - 1);
+    let s0 = samples[i];
+    let s1 = match samples.get(i + 1) {
+        Some(s1) => *s1,
+        None if looping => samples[0],
+        None => s0
+    };
+
+    s0 + (s1 - s0) * (pos - i as f64).min(1.0)
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a voltage clamp electrode.
+#[derive(Clone, Debug)]
+pub struct VoltageClampElectrode {
+    /// Active electrode flag.
+    active: bool,
+    /// The length of time the electrode has been active.
+    a: f64,
+    /// The clamped neurite (weak, as the electrode is usually attached to it).
+    target: Weak<RefCell<Neurite>>,
+    /// The holding potential.
+    v: f64,
+    /// The step commands as (start time, duration, command potential), relative to the electrode turning on.
+    commands: Vec<(f64, f64, f64)>,
+    /// The feedback gain (inverse series resistance).
+    gain: f64,
+    /// Current output of the electrode (the clamp current).
+    y: f64,
+    /// Set if the clamp current is recorded.
+    recording: bool,
+    /// The recorded (time, clamp current) samples since the electrode turned on.
+    currents: Vec<(f64, f64)>
+}
+
+// PartialEq for VoltageClampElectrode (the target neurite is compared by identity)
+impl PartialEq for VoltageClampElectrode {
+    fn eq(&self, other: &Self) -> bool {
+        self.active == other.active && self.a == other.a && self.target.ptr_eq(&other.target) && self.v == other.v
+            && self.commands == other.commands && self.gain == other.gain && self.y == other.y
+            && self.recording == other.recording && self.currents == other.currents
+    }
+}
+
+// Electrode functions
+impl Electrode for VoltageClampElectrode {
+    /// Turns on (activates) the electrode.
+    fn on(&mut self) {
+        if !self.active {
+            self.currents.clear();
+        }
+
+        self.active = true;
+    }
+
+    /// Turns off (deactivates) the electrode.
+    fn off(&mut self) {
+        self.active = false;
+    }
+
+    /// Returns the voltage input of the electrode (the holding potential).
+    fn voltage(&self) -> f64 {
+        self.v
+    }
+
+    /// Sets the voltage input of the electrode (the holding potential).
+    fn set_voltage(&mut self, v: f64) {
+        self.v = v;
+    }
+
+    /// Returns the duration of the electrode's pulse (the end time of the last step command).
+    fn duration(&self) -> f64 {
+        self.commands.iter().map(|(start, d, _)| start + d).fold(0.0, f64::max)
+    }
+
+    /// Returns the current output of the electrode.
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    /// Processes and returns the next output of the electrode after the specified time step (ms). The clamp current
+    /// drives the target neurite toward the command potential in proportion to the feedback gain. The electrode must be
+    /// attached to the target neurite for the current to be injected. The output is 0 if the target no longer exists.
+    fn process(&mut self, time: f64) -> f64 {
+        if self.active {
+            let v = self.command(self.a);
+
+            self.y = self.target.upgrade().map_or(0.0, |target| self.gain * (v - target.borrow().v));
+
+            if self.recording {
+                self.currents.push((self.a, self.y));
+            }
+
+            self.a += time;
+            self.y
+        }
+        else {
+            self.a = 0.0;
+            self.y = 0.0;
+            0.0
+        }
+    }
+
+    /// Returns the model file record of the electrode, or none if the target no longer exists. The recorded clamp
+    /// currents are not saved.
+    fn record(&self, refs: &mut ModelRefs) -> Option<ElectrodeRecord> {
+        let target = self.target.upgrade()?;
+
+        Some(ElectrodeRecord::VoltageClamp(refs.neurite(&target), self.v, self.commands.clone(), self.gain))
+    }
+
+    /// Returns the dynamic state of the electrode.
+    fn state(&self) -> Option<ElectrodeState> {
+        Some(ElectrodeState::VoltageClamp(self.active, self.a, self.y, self.currents.clone()))
+    }
+
+    /// Restores the dynamic state of the electrode. If the state is not of this type of electrode, this returns an
+    /// error message.
+    fn restore(&mut self, state: &ElectrodeState) -> Result<(), &'static str> {
+        match state {
+            ElectrodeState::VoltageClamp(active, a, y, currents) => {
+                self.active = *active;
+                self.a = *a;
+                self.y = *y;
+                self.currents = currents.clone();
+                Ok(())
+            }
+            _ => Err("Electrode state does not match the electrode type.")
+        }
+    }
+}
+
+// VoltageClampElectrode functions
+impl VoltageClampElectrode {
+    /// Creates a new voltage clamp electrode for the specified neurite with the specified holding potential and
+    /// feedback gain. The gain times the time step should stay well below the neurite's capacitance for the clamp to
+    /// be stable. The clamp current is not recorded.
+    pub fn new(target: &Rc<RefCell<Neurite>>, v: f64, gain: f64) -> Self {
+        Self {
+            active: false,
+            a: 0.0,
+            target: Rc::downgrade(target),
+            v,
+            commands: vec![],
+            gain,
+            y: 0.0,
+            recording: false,
+            currents: vec![]
+        }
+    }
+
+    /// Sets if the clamp current is recorded. Recording grows with every time step the electrode is on, so a probe is
+    /// preferable for long runs.
+    pub fn set_recording(&mut self, recording: bool) {
+        self.recording = recording;
+    }
+
+    /// Adds a step command to the specified potential, starting at the specified time (ms) after the electrode turns on
+    /// and lasting for the specified duration (ms).
+    pub fn add_command(&mut self, start: f64, duration: f64, v: f64) {
+        self.commands.push((start, duration, v));
+    }
+
+    /// Returns the command potential at the specified time (ms) after the electrode turned on. Later commands take
+    /// precedence over earlier overlapping commands.
+    pub fn command(&self, t: f64) -> f64 {
+        self.commands.iter()
+            .rev()
+            .find(|(start, d, _)| t >= *start && t < start + d)
+            .map_or(self.v, |(_, _, v)| *v)
+    }
+
+    /// Returns the recorded (time, clamp current) samples since the electrode turned on (if recording is set).
+    pub fn clamp_currents(&self) -> &[(f64, f64)] {
+        &self.currents
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a conductance (dynamic clamp) electrode.
+#[derive(Clone)]
+pub struct ConductanceElectrode {
+    /// Active electrode flag.
+    active: bool,
+    /// The electrode providing the conductance waveform.
+    g: Rc<RefCell<dyn Electrode>>,
+    /// The reversal potential.
+    e_rev: f64,
+    /// The neurite the conductance is injected into (weak, as the electrode is usually attached to it).
+    target: Weak<RefCell<Neurite>>,
+    /// Current output of the electrode.
+    y: f64
+}
+
+// Debug for ConductanceElectrode (the conductance electrode and target neurite are not printed)
+impl std::fmt::Debug for ConductanceElectrode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConductanceElectrode")
+            .field("active", &self.active)
+            .field("e_rev", &self.e_rev)
+            .field("y", &self.y)
+            .finish_non_exhaustive()
+    }
+}
+
+// PartialEq for ConductanceElectrode (the conductance electrode and target neurite are compared by identity)
+impl PartialEq for ConductanceElectrode {
+    fn eq(&self, other: &Self) -> bool {
+        self.active == other.active && Rc::ptr_eq(&self.g, &other.g) && self.e_rev == other.e_rev
+            && self.target.ptr_eq(&other.target) && self.y == other.y
+    }
+}
+
+// Electrode functions
+impl Electrode for ConductanceElectrode {
+    /// Turns on (activates) the electrode and its conductance electrode.
+    fn on(&mut self) {
+        self.active = true;
+        self.g.borrow_mut().on();
+    }
+
+    /// Turns off (deactivates) the electrode and its conductance electrode.
+    fn off(&mut self) {
+        self.active = false;
+        self.g.borrow_mut().off();
+    }
+
+    /// Returns the voltage input of the electrode (the reversal potential).
+    fn voltage(&self) -> f64 {
+        self.e_rev
+    }
+
+    /// Sets the voltage input of the electrode (the reversal potential).
+    fn set_voltage(&mut self, v: f64) {
+        self.e_rev = v;
+    }
+
+    /// Returns the duration of the electrode's pulse (the duration of the conductance electrode).
+    fn duration(&self) -> f64 {
+        self.g.borrow().duration()
+    }
+
+    /// Returns the current output of the electrode.
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    /// Processes and returns the next output of the electrode after the specified time step (ms): the conductance
+    /// times the driving force g(t) * (E_rev - v) of the target neurite. The conductance electrode is not processed
+    /// here and must be processed before this electrode (added to a simulation first). The electrode must be attached
+    /// to the target neurite for the current to be injected.
+    fn process(&mut self, _time: f64) -> f64 {
+        if self.active {
+            let g = self.g.borrow().output();
+
+            self.y = self.target.upgrade().map_or(0.0, |target| g * (self.e_rev - target.borrow().v));
+            self.y
+        }
+        else {
+            self.y = 0.0;
+            0.0
+        }
+    }
+
+    /// Seeds the conductance electrode's random number generator.
+    fn seed(&mut self, rng: SimRng) {
+        self.g.borrow_mut().seed(rng);
+    }
+
+    /// Returns the model file record of the electrode, or none if the target no longer exists.
+    fn record(&self, refs: &mut ModelRefs) -> Option<ElectrodeRecord> {
+        let target = self.target.upgrade()?;
+
+        Some(ElectrodeRecord::Conductance(refs.electrode(&self.g), self.e_rev, refs.neurite(&target)))
+    }
+
+    /// Returns the dynamic state of the electrode. The conductance electrode has its own state.
+    fn state(&self) -> Option<ElectrodeState> {
+        Some(ElectrodeState::Conductance(self.active, self.y))
+    }
+
+    /// Restores the dynamic state of the electrode. If the state is not of this type of electrode, this returns an
+    /// error message.
+    fn restore(&mut self, state: &ElectrodeState) -> Result<(), &'static str> {
+        match state {
+            ElectrodeState::Conductance(active, y) => {
+                self.active = *active;
+                self.y = *y;
+                Ok(())
+            }
+            _ => Err("Electrode state does not match the electrode type.")
+        }
+    }
+}
+
+// ConductanceElectrode functions
+impl ConductanceElectrode {
+    /// Creates a new conductance electrode with the specified conductance electrode, reversal potential and target
+    /// neurite.
+    pub fn new(g: Rc<RefCell<dyn Electrode>>, e_rev: f64, target: &Rc<RefCell<Neurite>>) -> Self {
+        Self {
+            active: false,
+            g,
+            e_rev,
+            target: Rc::downgrade(target),
+            y: 0.0
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a time series sensor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeSeriesSensor {
+    /// The length of time the sensor has been processed.
+    a: f64,
+    /// The time series samples.
+    samples: Vec<f64>,
+    /// The time between samples (ms).
+    si: f64,
+    /// Set if the time series loops.
+    looping: bool,
+    /// Current output of the sensor.
+    y: f64
+}
+
+// Sensor functions
+impl Sensor for TimeSeriesSensor {
+    /// Returns the current output of the sensor.
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    /// Processes and returns the next output of the sensor after the specified time step (ms). The time series is
+    /// linearly interpolated, and is 0 after its end unless it loops.
+    fn process(&mut self, time: f64) -> f64 {
+        let length = self.samples.len() as f64 * self.si;
+        let t = self.a;
+        self.a += time;
+
+        if self.samples.is_empty() || (t >= length && !self.looping) {
+            self.y = 0.0;
+            return 0.0;
+        }
+
+        self.y = interpolate(&self.samples, self.si, t % length, self.looping);
+        self.y
+    }
+
+    /// Returns the dynamic state of the sensor.
+    fn state(&self) -> SensorState {
+        let mut state = SensorState::new(self.y);
+        state.a = self.a;
+
+        state
+    }
+
+    /// Restores the dynamic state of the sensor.
+    fn restore(&mut self, state: &SensorState) {
+        self.a = state.a;
+        self.y = state.y;
+    }
+}
+
+// TimeSeriesSensor functions
+impl TimeSeriesSensor {
+    /// Creates a new time series sensor with the specified samples and time between samples (ms). If the time between
+    /// samples is not positive and finite, this returns an error message.
+    pub fn new(samples: Vec<f64>, si: f64) -> Result<Self, &'static str> {
+        check_sample_interval(si)?;
+
+        Ok(Self {
+            a: 0.0,
+            samples,
+            si,
+            looping: false,
+            y: 0.0
+        })
+    }
+
+    /// Creates a new time series sensor from the specified (zero based) column of a CSV file and time between samples
+    /// (ms).
+    pub fn from_csv(path: &str, column: usize, si: f64) -> Result<Self, String> {
+        Ok(Self::new(read_csv_column(path, column)?, si)?)
+    }
+
+    /// Sets if the time series loops.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Appends the specified samples to the time series (such as samples streamed from an external source).
+    pub fn push_samples(&mut self, samples: &[f64]) {
+        self.samples.extend_from_slice(samples);
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a 2D receptive field sensor over a grayscale image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReceptiveFieldSensor {
+    /// The image width (pixels).
+    width: usize,
+    /// The image height (pixels).
+    height: usize,
+    /// The grayscale image (row major, width * height values).
+    image: Vec<f64>,
+    /// The receptive field weight of each pixel (difference of gaussians).
+    w: Vec<f64>,
+    /// Current output of the sensor.
+    y: f64
+}
+
+// Sensor functions
+impl Sensor for ReceptiveFieldSensor {
+    /// Returns the current output of the sensor.
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    /// Processes and returns the next output of the sensor after the specified time step (ms): the image weighted by
+    /// the receptive field.
+    fn process(&mut self, _time: f64) -> f64 {
+        self.y = self.image.iter().zip(&self.w).map(|(p, w)| p * w).sum();
+        self.y
+    }
+
+    /// Returns the dynamic state of the sensor.
+    fn state(&self) -> SensorState {
+        SensorState::new(self.y)
+    }
+
+    /// Restores the dynamic state of the sensor.
+    fn restore(&mut self, state: &SensorState) {
+        self.y = state.y;
+    }
+}
+
+// ReceptiveFieldSensor functions
+impl ReceptiveFieldSensor {
+    /// Creates a new receptive field sensor for images of the specified size (pixels) with a difference of gaussians
+    /// receptive field at the specified center (pixels). The center and surround gaussians are normalized and the
+    /// surround is scaled by the specified surround strength (0 for a center only receptive field).
+    pub fn new(width: usize, height: usize, cx: f64, cy: f64, sigma_c: f64, sigma_s: f64, k_s: f64) -> Self {
+        let gaussian = |sigma: f64| -> Vec<f64> {
+            let g: Vec<f64> = (0..width * height)
+                .map(|i| {
+                    let dx = (i % width) as f64 - cx;
+                    let dy = (i / width) as f64 - cy;
+                    (-(dx * dx + dy * dy) / (2.0 * sigma * sigma)).exp()
+                })
+                .collect();
+            let sum: f64 = g.iter().sum();
+
+            g.iter().map(|g| if sum > 0.0 { g / sum } else { 0.0 }).collect()
+        };
+
+        let center = gaussian(sigma_c);
+        let w = if k_s != 0.0 && sigma_s > 0.0 {
+            center.iter().zip(gaussian(sigma_s)).map(|(c, s)| c - k_s * s).collect()
+        }
+        else {
+            center
+        };
+
+        Self {
+            width,
+            height,
+            image: vec![0.0; width * height],
+            w,
+            y: 0.0
+        }
+    }
+
+    /// Sets the grayscale image (row major, width * height values). If the image is the wrong size, this returns an
+    /// error message.
+    pub fn set_image(&mut self, image: Vec<f64>) -> Result<(), &'static str> {
+        if image.len() != self.width * self.height {
+            return Err("Image size does not match the receptive field sensor size.");
+        }
+
+        self.image = image;
+
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a threshold sensor driven by an external variable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThresholdSensor {
+    /// The external variable.
+    var: Rc<Cell<f64>>,
+    /// The threshold.
+    th: f64,
+    /// Current output of the sensor.
+    y: f64
+}
+
+// Sensor functions
+impl Sensor for ThresholdSensor {
+    /// Returns the current output of the sensor.
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    /// Processes and returns the next output of the sensor after the specified time step (ms): 1 while the external
+    /// variable is at or above the threshold, otherwise 0.
+    fn process(&mut self, _time: f64) -> f64 {
+        self.y = if self.var.get() >= self.th { 1.0 } else { 0.0 };
+        self.y
+    }
+
+    /// Returns the dynamic state of the sensor.
+    fn state(&self) -> SensorState {
+        SensorState::new(self.y)
+    }
+
+    /// Restores the dynamic state of the sensor.
+    fn restore(&mut self, state: &SensorState) {
+        self.y = state.y;
+    }
+}
+
+// ThresholdSensor functions
+impl ThresholdSensor {
+    /// Creates a new threshold sensor with the specified external variable and threshold.
+    pub fn new(var: Rc<Cell<f64>>, th: f64) -> Self {
+        Self {
+            var,
+            th,
+            y: 0.0
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a rate sensor driven by an external variable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateSensor {
+    /// The external variable.
+    var: Rc<Cell<f64>>,
+    /// The rate gain.
+    gain: f64,
+    /// The maximum rate.
+    max: f64,
+    /// Current output of the sensor.
+    y: f64
+}
+
+// Sensor functions
+impl Sensor for RateSensor {
+    /// Returns the current output of the sensor.
+    fn output(&self) -> f64 {
+        self.y
+    }
+
+    /// Processes and returns the next output of the sensor after the specified time step (ms): the external variable
+    /// times the gain, clamped to 0..max.
+    fn process(&mut self, _time: f64) -> f64 {
+        self.y = (self.gain * self.var.get()).max(0.0).min(self.max);
+        self.y
+    }
+
+    /// Returns the dynamic state of the sensor.
+    fn state(&self) -> SensorState {
+        SensorState::new(self.y)
+    }
+
+    /// Restores the dynamic state of the sensor.
+    fn restore(&mut self, state: &SensorState) {
+        self.y = state.y;
+    }
+}
+
+// RateSensor functions
+impl RateSensor {
+    /// Creates a new rate sensor with the specified external variable, gain and maximum rate.
+    pub fn new(var: Rc<Cell<f64>>, gain: f64, max: f64) -> Self {
+        Self {
+            var,
+            gain,
+            max,
+            y: 0.0
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for an excitatory synapse.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExcitatorySynapse {
+    /// The synaptic ID.
+    id: SynapticID,
+    /// The type of synapse.
+    syn_type: SynapseType,
+    /// The presynaptic input.
+    x_pre: PresynapticInput,
+    /// The postsynaptic neurite.
+    x_post: Rc<RefCell<Neurite>>,
+    /// A synaptic modulatory synapse (optional) connected to this synapse.
+    mod_syn: Option<Rc<RefCell<SynapticModulatorySynapse>>>,
+    /// Conduction delay of presynaptic spikes.
+    delay: SpikeDelay,
+    /// The input conductance of the synapse (total input).
+    x: f64,
+    /// Conductance decay time.
+    tx: f64,
+    /// Short-term plasticity data.
+    stp: ShortTermPlasticity,
+    /// Long-term plasticity data (optional).
+    ltp: Option<LongTermPlasticity>,
+    /// Synaptic weight.
+    w: f64,
+    /// Maximal synaptic input conductance (maximal input).
+    x_max: f64
+}
+
+// Synapse functions for ExcitatorySynapse
+impl Synapse for ExcitatorySynapse {
+    /// Returns the synaptic ID.
+    fn syn_id(&self) -> SynapticID {
+        self.id
+    }
+
+    /// Returns the synaptic type.
+    fn syn_type(&self) -> SynapseType {
+        self.syn_type
+    }
+
+    /// Returns the presynaptic input.
+    fn x_pre(&self) -> PresynapticInput {
+        self.x_pre.clone()
+    }
+
+    /// Returns the current total synaptic input.
+    fn input(&self) -> f64 {
+        modulated(&self.mod_syn, SynapticModVar::X, self.x)
+    }
+
+    /// Sets the synaptic modulatory synapse (optional) connected to this synapse.
+    fn set_mod_syn(&mut self, mod_syn: Option<Rc<RefCell<SynapticModulatorySynapse>>>) {
+        self.mod_syn = mod_syn;
+    }
+
+    /// Returns the current value of the specified variable, or none if the synapse does not have the variable.
+    fn probe(&self, var: SynapseVar) -> Option<f64> {
+        match var {
+            SynapseVar::X => Some(self.x),
+            SynapseVar::W => Some(self.w),
+            _ => self.stp.probe(var)
+        }
+    }
+
+    /// Processes and returns the next total input of the synapse after the specified time step (ms).
+    fn process(&mut self, time: f64) -> f64 {
+        let pre = self.delay.process(self.x_pre.spike(), time);
+
+        if let Some(ltp) = self.ltp.as_mut() {
+            self.w = ltp.learn(pre, self.x_post.borrow().y > 0.0, self.w, time);
+        }
+
+        let tx = modulated(&self.mod_syn, SynapticModVar::TX, self.tx);
+        let x_max = modulated(&self.mod_syn, SynapticModVar::G, self.x_max);
+        let w = modulated(&self.mod_syn, SynapticModVar::W, self.w);
+        let p = self.stp.p;
+
+        self.x *= (-time / tx).exp();
+        self.stp.p = modulated(&self.mod_syn, SynapticModVar::P, p);
+        self.stp.learn(pre, time);
+        self.stp.p = p;
+        self.x += x_max * self.stp.y * w;
+
+        self.input()
+    }
+
+    synapse_functions!(record: [tx, w, x_max], state: [x, w], parts: [stp, delay, ltp]);
+}
+
+// ExcitatorySynapse functions
+impl ExcitatorySynapse {
+    /// Creates a new excitatory synapse with the specified parameters. The synaptic weight starts at 1.
+    pub fn new(x_pre: PresynapticInput, x_post: Rc<RefCell<Neurite>>, stp: ShortTermPlasticity, tx: f64, x_max: f64) -> Self {
+        Self {
+            id: SynapticID::new(),
+            syn_type: SynapseType::Excitatory,
+            x_pre,
+            x_post,
+            mod_syn: None,
+            delay: SpikeDelay::new(0.0),
+            x: 0.0,
+            tx,
+            stp,
+            ltp: None,
+            w: 1.0,
+            x_max
+        }
+    }
+
+    /// Creates a new excitatory synapse from the specified model file record, presynaptic input, postsynaptic neurite
+    /// and long-term plasticity data (optional). The record must have short-term plasticity data.
+    pub fn from_record(record: &SynapseRecord, x_pre: PresynapticInput, x_post: Rc<RefCell<Neurite>>, ltp: Option<LongTermPlasticity>) -> Self {
+        Self {
+            id: record.id,
+            syn_type: SynapseType::Excitatory,
+            x_pre,
+            x_post,
+            mod_syn: None,
+            delay: SpikeDelay::new(record.delay),
+            x: 0.0,
+            tx: record.tx,
+            stp: record.stp.clone().expect("short-term plasticity data"),
+            ltp,
+            w: record.w,
+            x_max: record.x_max
+        }
+    }
+
+    /// Returns the synaptic weight.
+    pub fn w(&self) -> f64 {
+        self.w
+    }
+
+    /// Sets the synaptic weight.
+    pub fn set_w(&mut self, w: f64) {
+        self.w = w;
+    }
+
+    /// Sets the long-term plasticity data (optional).
+    pub fn set_ltp(&mut self, ltp: Option<LongTermPlasticity>) {
+        self.ltp = ltp;
+    }
+
+    /// Sets the conduction delay of presynaptic spikes (ms). Spikes in transit are discarded.
+    pub fn set_delay(&mut self, delay: f64) {
+        self.delay = SpikeDelay::new(delay);
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for an inhibitory synapse.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InhibitorySynapse {
+    /// The synaptic ID.
+    id: SynapticID,
+    /// The type of synapse.
+    syn_type: SynapseType,
+    /// The presynaptic input.
+    x_pre: PresynapticInput,
+    /// The postsynaptic neurite.
+    x_post: Rc<RefCell<Neurite>>,
+    /// A synaptic modulatory synapse (optional) connected to this synapse.
+    mod_syn: Option<Rc<RefCell<SynapticModulatorySynapse>>>,
+    /// Conduction delay of presynaptic spikes.
+    delay: SpikeDelay,
+    /// The input conductance of the synapse (total input).
+    x: f64,
+    /// Conductance decay time.
+    tx: f64,
+    /// Short-term plasticity data.
+    stp: ShortTermPlasticity,
+    /// Long-term plasticity data (optional).
+    ltp: Option<LongTermPlasticity>,
+    /// Synaptic weight.
+    w: f64,
+    /// Maximal synaptic input conductance (maximal input).
+    x_max: f64
+}
+
+// Synapse functions for InhibitorySynapse
+impl Synapse for InhibitorySynapse {
+    /// Returns the synaptic ID.
+    fn syn_id(&self) -> SynapticID {
+        self.id
+    }
+
+    /// Returns the synaptic type.
+    fn syn_type(&self) -> SynapseType {
+        self.syn_type
+    }
+
+    /// Returns the presynaptic input.
+    fn x_pre(&self) -> PresynapticInput {
+        self.x_pre.clone()
+    }
+
+    /// Returns the current total synaptic input. Inhibitory input is negative.
+    fn input(&self) -> f64 {
+        -modulated(&self.mod_syn, SynapticModVar::X, self.x)
+    }
+
+    /// Sets the synaptic modulatory synapse (optional) connected to this synapse.
+    fn set_mod_syn(&mut self, mod_syn: Option<Rc<RefCell<SynapticModulatorySynapse>>>) {
+        self.mod_syn = mod_syn;
+    }
+
+    /// Returns the current value of the specified variable, or none if the synapse does not have the variable.
+    fn probe(&self, var: SynapseVar) -> Option<f64> {
+        match var {
+            SynapseVar::X => Some(self.x),
+            SynapseVar::W => Some(self.w),
+            _ => self.stp.probe(var)
+        }
+    }
+
+    /// Processes and returns the next total input of the synapse after the specified time step (ms).
+    fn process(&mut self, time: f64) -> f64 {
+        let pre = self.delay.process(self.x_pre.spike(), time);
+
+        if let Some(ltp) = self.ltp.as_mut() {
+            self.w = ltp.learn(pre, self.x_post.borrow().y > 0.0, self.w, time);
+        }
+
+        let tx = modulated(&self.mod_syn, SynapticModVar::TX, self.tx);
+        let x_max = modulated(&self.mod_syn, SynapticModVar::G, self.x_max);
+        let w = modulated(&self.mod_syn, SynapticModVar::W, self.w);
+        let p = self.stp.p;
+
+        self.x *= (-time / tx).exp();
+        self.stp.p = modulated(&self.mod_syn, SynapticModVar::P, p);
+        self.stp.learn(pre, time);
+        self.stp.p = p;
+        self.x += x_max * self.stp.y * w;
+
+        self.input()
+    }
+
+    synapse_functions!(record: [tx, w, x_max], state: [x, w], parts: [stp, delay, ltp]);
 }
 
 // InhibitorySynapse functions
 impl InhibitorySynapse {
-    /// Creates a new fast inhibitory synapse with the specified parameters.
+    /// Creates a new fast inhibitory synapse with the specified parameters. The synaptic weight starts at 1.
     pub fn new(x_pre: PresynapticInput, x_post: Rc<RefCell<Neurite>>, stp: ShortTermPlasticity, tx: f64, x_max: f64) -> Self {
         Self {
             id: SynapticID::new(),
@@ -641,13 +2756,54 @@ impl InhibitorySynapse {
             x_pre,
             x_post,
             mod_syn: None,
+            delay: SpikeDelay::new(0.0),
             x: 0.0,
             tx,
             stp,
-            w: 0.0,
+            ltp: None,
+            w: 1.0,
             x_max
         }
     }
+
+    /// Creates a new inhibitory synapse from the specified model file record, presynaptic input, postsynaptic neurite
+    /// and long-term plasticity data (optional). The record must have short-term plasticity data.
+    pub fn from_record(record: &SynapseRecord, x_pre: PresynapticInput, x_post: Rc<RefCell<Neurite>>, ltp: Option<LongTermPlasticity>) -> Self {
+        Self {
+            id: record.id,
+            syn_type: SynapseType::Inhibitory,
+            x_pre,
+            x_post,
+            mod_syn: None,
+            delay: SpikeDelay::new(record.delay),
+            x: 0.0,
+            tx: record.tx,
+            stp: record.stp.clone().expect("short-term plasticity data"),
+            ltp,
+            w: record.w,
+            x_max: record.x_max
+        }
+    }
+
+    /// Returns the synaptic weight.
+    pub fn w(&self) -> f64 {
+        self.w
+    }
+
+    /// Sets the synaptic weight.
+    pub fn set_w(&mut self, w: f64) {
+        self.w = w;
+    }
+
+    /// Sets the long-term plasticity data (optional).
+    pub fn set_ltp(&mut self, ltp: Option<LongTermPlasticity>) {
+        self.ltp = ltp;
+    }
+
+    /// Sets the conduction delay of presynaptic spikes (ms). Spikes in transit are discarded.
+    pub fn set_delay(&mut self, delay: f64) {
+        self.delay = SpikeDelay::new(delay);
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -665,38 +2821,162 @@ pub struct ShuntingInhibitorySynapse {
     x_post: Rc<RefCell<Neurite>>,
     /// A synaptic modulatory synapse (optional) connected to this synapse.
     mod_syn: Option<Rc<RefCell<SynapticModulatorySynapse>>>,
+    /// Conduction delay of presynaptic spikes.
+    delay: SpikeDelay,
     /// The input conductance of the synapse (total input).
     x: f64,
     /// Conductance decay time.
     tx: f64,
     /// The shunting scalar (-2..1).
     s: f64,
+    /// The reversal potential of the synapse (mV).
+    e_rev: f64,
+    /// The postsynaptic membrane potential at the start of the time step (mV).
+    v_post: f64,
     /// Short-term plasticity data.
     stp: ShortTermPlasticity,
+    /// Long-term plasticity data (optional).
+    ltp: Option<LongTermPlasticity>,
     /// Synaptic weight.
     w: f64,
     /// Maximal synaptic input conductance (maximal input).
     x_max: f64
 }
 
+// Synapse functions for ShuntingInhibitorySynapse
+impl Synapse for ShuntingInhibitorySynapse {
+    /// Returns the synaptic ID.
+    fn syn_id(&self) -> SynapticID {
+        self.id
+    }
+
+    /// Returns the synaptic type.
+    fn syn_type(&self) -> SynapseType {
+        self.syn_type
+    }
+
+    /// Returns the presynaptic input.
+    fn x_pre(&self) -> PresynapticInput {
+        self.x_pre.clone()
+    }
+
+    /// Returns the current total synaptic input, s * x * (v - E_rev) for the postsynaptic membrane potential v at the
+    /// start of the time step. Negative scalars shunt the membrane potential toward the reversal potential (with no
+    /// effect at the reversal potential) and positive scalars drive it away.
+    fn input(&self) -> f64 {
+        self.s * modulated(&self.mod_syn, SynapticModVar::X, self.x) * (self.v_post - self.e_rev)
+    }
+
+    /// Sets the synaptic modulatory synapse (optional) connected to this synapse.
+    fn set_mod_syn(&mut self, mod_syn: Option<Rc<RefCell<SynapticModulatorySynapse>>>) {
+        self.mod_syn = mod_syn;
+    }
+
+    /// Returns the current value of the specified variable, or none if the synapse does not have the variable.
+    fn probe(&self, var: SynapseVar) -> Option<f64> {
+        match var {
+            SynapseVar::X => Some(self.x),
+            SynapseVar::W => Some(self.w),
+            _ => self.stp.probe(var)
+        }
+    }
+
+    /// Processes and returns the next total input of the synapse after the specified time step (ms).
+    fn process(&mut self, time: f64) -> f64 {
+        let pre = self.delay.process(self.x_pre.spike(), time);
+        self.v_post = self.x_post.borrow().v;
+
+        if let Some(ltp) = self.ltp.as_mut() {
+            self.w = ltp.learn(pre, self.x_post.borrow().y > 0.0, self.w, time);
+        }
+
+        let tx = modulated(&self.mod_syn, SynapticModVar::TX, self.tx);
+        let x_max = modulated(&self.mod_syn, SynapticModVar::G, self.x_max);
+        let w = modulated(&self.mod_syn, SynapticModVar::W, self.w);
+        let p = self.stp.p;
+
+        self.x *= (-time / tx).exp();
+        self.stp.p = modulated(&self.mod_syn, SynapticModVar::P, p);
+        self.stp.learn(pre, time);
+        self.stp.p = p;
+        self.x += x_max * self.stp.y * w;
+
+        self.input()
+    }
+
+    synapse_functions!(record: [tx, s, e_rev, w, x_max], state: [x, w], parts: [stp, delay, ltp]);
+}
+
 // ShuntingInhibitorySynapse functions
 impl ShuntingInhibitorySynapse {
-    /// Creates a new shunting inhibitory synapse with the specified parameters.
-    pub fn new(x_pre: PresynapticInput, x_post: Rc<RefCell<Neurite>>, stp: ShortTermPlasticity, s: f64, tx: f64, x_max: f64) -> Self {
+    /// Creates a new shunting inhibitory synapse with the specified parameters and reversal potential (mV). The
+    /// synaptic weight starts at 1.
+    pub fn new(x_pre: PresynapticInput, x_post: Rc<RefCell<Neurite>>, stp: ShortTermPlasticity, s: f64, e_rev: f64, tx: f64,
+               x_max: f64) -> Self {
+        let v_post = x_post.borrow().v;
+
         Self {
             id: SynapticID::new(),
             syn_type: SynapseType::ShuntingInhibitory,
             x_pre,
             x_post,
             mod_syn: None,
+            delay: SpikeDelay::new(0.0),
             x: 0.0,
             tx,
-            s: 1.0.min(-2.0.max(s)),
+            s: s.clamp(-2.0, 1.0),
+            e_rev,
+            v_post,
             stp,
-            w: 0.0,
+            ltp: None,
+            w: 1.0,
             x_max
         }
     }
+
+    /// Creates a new shunting inhibitory synapse from the specified model file record, presynaptic input, postsynaptic
+    /// neurite and long-term plasticity data (optional). The record must have short-term plasticity data.
+    pub fn from_record(record: &SynapseRecord, x_pre: PresynapticInput, x_post: Rc<RefCell<Neurite>>, ltp: Option<LongTermPlasticity>) -> Self {
+        let v_post = x_post.borrow().v;
+
+        Self {
+            id: record.id,
+            syn_type: SynapseType::ShuntingInhibitory,
+            x_pre,
+            x_post,
+            mod_syn: None,
+            delay: SpikeDelay::new(record.delay),
+            x: 0.0,
+            tx: record.tx,
+            s: record.s,
+            e_rev: record.e_rev,
+            v_post,
+            stp: record.stp.clone().expect("short-term plasticity data"),
+            ltp,
+            w: record.w,
+            x_max: record.x_max
+        }
+    }
+
+    /// Returns the synaptic weight.
+    pub fn w(&self) -> f64 {
+        self.w
+    }
+
+    /// Sets the synaptic weight.
+    pub fn set_w(&mut self, w: f64) {
+        self.w = w;
+    }
+
+    /// Sets the long-term plasticity data (optional).
+    pub fn set_ltp(&mut self, ltp: Option<LongTermPlasticity>) {
+        self.ltp = ltp;
+    }
+
+    /// Sets the conduction delay of presynaptic spikes (ms). Spikes in transit are discarded.
+    pub fn set_delay(&mut self, delay: f64) {
+        self.delay = SpikeDelay::new(delay);
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -714,12 +2994,69 @@ pub struct SynapticModulatorySynapse {
     x_post: Rc<RefCell<Neurite>>,
     /// A synaptic modulatory synapse (optional) connected to this synapse.
     mod_syn: Option<Rc<RefCell<SynapticModulatorySynapse>>>,
+    /// Conduction delay of presynaptic spikes.
+    delay: SpikeDelay,
     /// The input conductance of the synapse (total input).
     x: f64,
     /// Conductance decay time.
     tx: f64,
     /// Short-term plasticity data.
-    stp: ShortTermPlasticity
+    stp: ShortTermPlasticity,
+    /// Modulation of the target synaptic variable.
+    modulation: Modulation
+}
+
+// Synapse functions for SynapticModulatorySynapse
+impl Synapse for SynapticModulatorySynapse {
+    /// Returns the synaptic ID.
+    fn syn_id(&self) -> SynapticID {
+        self.id
+    }
+
+    /// Returns the synaptic type.
+    fn syn_type(&self) -> SynapseType {
+        self.syn_type
+    }
+
+    /// Returns the presynaptic input.
+    fn x_pre(&self) -> PresynapticInput {
+        self.x_pre.clone()
+    }
+
+    /// Returns the current total synaptic input (the modulation level).
+    fn input(&self) -> f64 {
+        modulated(&self.mod_syn, SynapticModVar::X, self.x)
+    }
+
+    /// Sets the synaptic modulatory synapse (optional) connected to this synapse.
+    fn set_mod_syn(&mut self, mod_syn: Option<Rc<RefCell<SynapticModulatorySynapse>>>) {
+        self.mod_syn = mod_syn;
+    }
+
+    /// Returns the current value of the specified variable, or none if the synapse does not have the variable.
+    fn probe(&self, var: SynapseVar) -> Option<f64> {
+        match var {
+            SynapseVar::X => Some(self.x),
+            _ => self.stp.probe(var)
+        }
+    }
+
+    /// Processes and returns the next total input of the synapse after the specified time step (ms).
+    fn process(&mut self, time: f64) -> f64 {
+        let tx = modulated(&self.mod_syn, SynapticModVar::TX, self.tx);
+        let p = self.stp.p;
+
+        self.x *= (-time / tx).exp();
+        self.stp.p = modulated(&self.mod_syn, SynapticModVar::P, p);
+        let pre = self.delay.process(self.x_pre.spike(), time);
+        self.stp.learn(pre, time);
+        self.stp.p = p;
+        self.x += self.stp.y;
+
+        self.input()
+    }
+
+    synapse_functions!(record: [tx], state: [x], parts: [stp, delay, modulation]);
 }
 
 // SynapticModulatorySynapse functions
@@ -732,9 +3069,47 @@ impl SynapticModulatorySynapse {
             x_pre,
             x_post,
             mod_syn: None,
+            delay: SpikeDelay::new(0.0),
             x: 0.0,
             tx,
-            stp
+            stp,
+            modulation: Modulation::default()
+        }
+    }
+
+    /// Creates a new synaptic modulatory synapse from the specified model file record, presynaptic input and
+    /// postsynaptic neurite. The record must have short-term plasticity data.
+    pub fn from_record(record: &SynapseRecord, x_pre: PresynapticInput, x_post: Rc<RefCell<Neurite>>) -> Self {
+        Self {
+            id: record.id,
+            syn_type: record.syn_type,
+            x_pre,
+            x_post,
+            mod_syn: None,
+            delay: SpikeDelay::new(record.delay),
+            x: 0.0,
+            tx: record.tx,
+            stp: record.stp.clone().expect("short-term plasticity data"),
+            modulation: record.modulation.unwrap_or_default()
+        }
+    }
+
+    /// Sets the modulation of the target synaptic variable.
+    pub fn set_modulation(&mut self, modulation: Modulation) {
+        self.modulation = modulation;
+    }
+
+    /// Sets the conduction delay of presynaptic spikes (ms). Spikes in transit are discarded.
+    pub fn set_delay(&mut self, delay: f64) {
+        self.delay = SpikeDelay::new(delay);
+    }
+
+    /// Returns the specified base value of a synaptic variable modulated by the current modulation level. If the
+    /// variable is not the target of this synapse, the base value is returned unchanged.
+    pub fn modulate(&self, var: SynapticModVar, base: f64) -> f64 {
+        match self.syn_type {
+            SynapseType::SynapticModulator(target) if target == var => self.modulation.apply(base, self.x),
+            _ => base
         }
     }
 }
@@ -754,12 +3129,76 @@ pub struct NeuralModulatorySynapse {
     x_post: Rc<RefCell<Neurite>>,
     /// A synaptic modulatory synapse (optional) connected to this synapse.
     mod_syn: Option<Rc<RefCell<SynapticModulatorySynapse>>>,
+    /// Conduction delay of presynaptic spikes.
+    delay: SpikeDelay,
     /// The input conductance of the synapse (total input).
     x: f64,
     /// Conductance decay time.
     tx: f64,
     /// Short-term plasticity data.
-    stp: ShortTermPlasticity
+    stp: ShortTermPlasticity,
+    /// Modulation of the target neurite variable.
+    modulation: Modulation
+}
+
+// Synapse functions for NeuralModulatorySynapse
+impl Synapse for NeuralModulatorySynapse {
+    /// Returns the synaptic ID.
+    fn syn_id(&self) -> SynapticID {
+        self.id
+    }
+
+    /// Returns the synaptic type.
+    fn syn_type(&self) -> SynapseType {
+        self.syn_type
+    }
+
+    /// Returns the presynaptic input.
+    fn x_pre(&self) -> PresynapticInput {
+        self.x_pre.clone()
+    }
+
+    /// Returns the current total synaptic input (the modulation level).
+    fn input(&self) -> f64 {
+        modulated(&self.mod_syn, SynapticModVar::X, self.x)
+    }
+
+    /// Sets the synaptic modulatory synapse (optional) connected to this synapse.
+    fn set_mod_syn(&mut self, mod_syn: Option<Rc<RefCell<SynapticModulatorySynapse>>>) {
+        self.mod_syn = mod_syn;
+    }
+
+    /// Returns the current value of the specified variable, or none if the synapse does not have the variable.
+    fn probe(&self, var: SynapseVar) -> Option<f64> {
+        match var {
+            SynapseVar::X => Some(self.x),
+            _ => self.stp.probe(var)
+        }
+    }
+
+    /// Processes and returns the next total input of the synapse after the specified time step (ms). The target
+    /// variable of the postsynaptic neurite is modulated by the new modulation level.
+    fn process(&mut self, time: f64) -> f64 {
+        let tx = modulated(&self.mod_syn, SynapticModVar::TX, self.tx);
+        let p = self.stp.p;
+
+        self.x *= (-time / tx).exp();
+        self.stp.p = modulated(&self.mod_syn, SynapticModVar::P, p);
+        let pre = self.delay.process(self.x_pre.spike(), time);
+        self.stp.learn(pre, time);
+        self.stp.p = p;
+        self.x += self.stp.y;
+
+        let level = self.input();
+
+        if let SynapseType::NeuralModulator(var) = self.syn_type {
+            self.x_post.borrow_mut().modulate(var, &self.modulation, level, time);
+        }
+
+        level
+    }
+
+    synapse_functions!(record: [tx], state: [x], parts: [stp, delay, modulation]);
 }
 
 // NeuralModulatorySynapse functions
@@ -772,9 +3211,45 @@ impl NeuralModulatorySynapse {
             x_pre,
             x_post,
             mod_syn: None,
+            delay: SpikeDelay::new(0.0),
             x: 0.0,
             tx,
-            stp
+            stp,
+            modulation: Modulation::default()
+        }
+    }
+
+    /// Creates a new neural modulatory synapse from the specified model file record, presynaptic input and postsynaptic
+    /// neurite. The record must have short-term plasticity data.
+    pub fn from_record(record: &SynapseRecord, x_pre: PresynapticInput, x_post: Rc<RefCell<Neurite>>) -> Self {
+        Self {
+            id: record.id,
+            syn_type: record.syn_type,
+            x_pre,
+            x_post,
+            mod_syn: None,
+            delay: SpikeDelay::new(record.delay),
+            x: 0.0,
+            tx: record.tx,
+            stp: record.stp.clone().expect("short-term plasticity data"),
+            modulation: record.modulation.unwrap_or_default()
+        }
+    }
+
+    /// Sets the modulation of the target neurite variable.
+    pub fn set_modulation(&mut self, modulation: Modulation) {
+        self.modulation = modulation;
+    }
+
+    /// Sets the conduction delay of presynaptic spikes (ms). Spikes in transit are discarded.
+    pub fn set_delay(&mut self, delay: f64) {
+        self.delay = SpikeDelay::new(delay);
+    }
+
+    /// Restores the target neurite variable to its value before it was first modulated.
+    pub fn unmodulate(&mut self) {
+        if let SynapseType::NeuralModulator(var) = self.syn_type {
+            self.x_post.borrow_mut().unmodulate(var);
         }
     }
 }
@@ -798,18 +3273,91 @@ pub struct GapJunctionSynapse {
     ax: f64,
     /// The conductance of the synapse from input b.
     bx: f64,
-    /// Short-term plasticity data.
-    stp: ShortTermPlasticity,
+    /// The neurite ID of input a (the presynaptic neurite), or none if input a is not a neurite.
+    a_id: Option<NeuriteID>,
+    /// The neurite ID of input b (the postsynaptic neurite).
+    b_id: NeuriteID,
     /// Synaptic weight.
     w: f64,
     /// Maximal synaptic input conductance (maximal input).
     x_max: f64
 }
 
+// Synapse functions for GapJunctionSynapse
+impl Synapse for GapJunctionSynapse {
+    /// Returns the synaptic ID.
+    fn syn_id(&self) -> SynapticID {
+        self.id
+    }
+
+    /// Returns the synaptic type.
+    fn syn_type(&self) -> SynapseType {
+        self.syn_type
+    }
+
+    /// Returns the presynaptic input.
+    fn x_pre(&self) -> PresynapticInput {
+        self.x_pre.clone()
+    }
+
+    /// Returns the current total synaptic input into the postsynaptic neurite (from input a).
+    fn input(&self) -> f64 {
+        self.ax
+    }
+
+    /// Returns the current synaptic input delivered to the specified neurite. The postsynaptic neurite (input b)
+    /// receives ax, the presynaptic neurite (input a) receives bx and any other neurite receives no input.
+    fn input_to(&self, id: NeuriteID) -> f64 {
+        if id == self.b_id {
+            self.ax
+        }
+        else if Some(id) == self.a_id {
+            self.bx
+        }
+        else {
+            0.0
+        }
+    }
+
+    /// Sets the synaptic modulatory synapse (optional) connected to this synapse.
+    fn set_mod_syn(&mut self, mod_syn: Option<Rc<RefCell<SynapticModulatorySynapse>>>) {
+        self.mod_syn = mod_syn;
+    }
+
+    /// Returns the current value of the specified variable, or none if the synapse does not have the variable.
+    fn probe(&self, var: SynapseVar) -> Option<f64> {
+        match var {
+            SynapseVar::AX => Some(self.ax),
+            SynapseVar::BX => Some(self.bx),
+            SynapseVar::W => Some(self.w),
+            _ => None
+        }
+    }
+
+    /// Processes and returns the next total input of the synapse after the specified time step (ms). Current
+    /// flows in both directions: ax flows from input a (presynaptic) into input b (postsynaptic) and bx flows
+    /// from input b into input a.
+    fn process(&mut self, _time: f64) -> f64 {
+        let va = self.x_pre.value();
+        let vb = self.x_post.borrow().v;
+        let g = modulated(&self.mod_syn, SynapticModVar::G, self.x_max) * modulated(&self.mod_syn, SynapticModVar::W, self.w);
+
+        self.ax = g * (va - vb);
+        self.bx = g * (vb - va);
+
+        self.ax
+    }
+
+    synapse_functions!(record: [w, x_max], state: [ax, bx, w], parts: []);
+}
+
 // GapJunctionSynapse functions
 impl GapJunctionSynapse {
-    /// Creates a new gap junction synapse with the specified parameters.
-    pub fn new(x_pre: PresynapticInput, x_post: Rc<RefCell<Neurite>>, stp: ShortTermPlasticity, x_max: f64) -> Self {
+    /// Creates a new gap junction synapse with the specified parameters. The synaptic weight starts at 1.
+    pub fn new(x_pre: PresynapticInput, x_post: Rc<RefCell<Neurite>>, x_max: f64) -> Self {
+        let a_id = x_pre.neurite_id();
+        let b_id = x_post.borrow().id;
+
         Self {
             id: SynapticID::new(),
             syn_type: SynapseType::Gap,
@@ -818,33 +3366,82 @@ impl GapJunctionSynapse {
             mod_syn: None,
             ax: 0.0,
             bx: 0.0,
-            stp,
-            w: 0.0,
+            a_id,
+            b_id,
+            w: 1.0,
             x_max
         }
     }
+
+    /// Creates a new gap junction synapse from the specified model file record, presynaptic input and postsynaptic
+    /// neurite.
+    pub fn from_record(record: &SynapseRecord, x_pre: PresynapticInput, x_post: Rc<RefCell<Neurite>>) -> Self {
+        let a_id = x_pre.neurite_id();
+        let b_id = x_post.borrow().id;
+
+        Self {
+            id: record.id,
+            syn_type: SynapseType::Gap,
+            x_pre,
+            x_post,
+            mod_syn: None,
+            ax: 0.0,
+            bx: 0.0,
+            a_id,
+            b_id,
+            w: record.w,
+            x_max: record.x_max
+        }
+    }
+
+    /// Returns the current input from input b (postsynaptic) into input a (presynaptic).
+    pub fn reverse_input(&self) -> f64 {
+        self.bx
+    }
+
+    /// Returns the synaptic weight.
+    pub fn w(&self) -> f64 {
+        self.w
+    }
+
+    /// Sets the synaptic weight.
+    pub fn set_w(&mut self, w: f64) {
+        self.w = w;
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
 
 /// Contains the neurite ID value for a neurite.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct NeuriteID {
     id: Uuid
 }
 
+// Default for NeuriteID
+impl Default for NeuriteID {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // NeuriteID function
 impl NeuriteID {
     /// Creates a new unique neurite ID value.
     pub fn new() -> Self {
         Self {
-            id: Uuid::new_v7(Timestamp::now(SystemTime::now()))
+            id: Uuid::now_v7()
         }
     }
+
+    /// Returns the UUID of the neurite ID.
+    pub fn uuid(&self) -> Uuid {
+        self.id
+    }
 }
 
 /// Contains a list of neurite types.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum NeuriteType {
     Soma,
     BasalProximal,
@@ -855,7 +3452,7 @@ pub enum NeuriteType {
 }
 
 /// Contains a list of spike models.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum SpikeModel {
     Accommodation,
     Bistability,
@@ -914,8 +3511,21 @@ pub enum SpikeModel {
 
 // -------------------------------------------------------------------------------------------------
 
+/// Contains a list of integration methods for the neurite equations.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Integrator {
+    /// Forward Euler (one step per time step).
+    Euler,
+    /// Two half steps of the membrane potential per time step, as in Izhikevich's original code.
+    HalfStep,
+    /// Classical fourth order Runge-Kutta.
+    RK4,
+    /// Adaptive Dormand-Prince Runge-Kutta 4(5) with the specified error tolerance.
+    RK45(f64)
+}
+
 /// Contains data for a neurite (neuron compartment).
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone)]
 pub struct Neurite {
     /// The neurite ID value.
     id: NeuriteID,
@@ -935,9 +3545,9 @@ pub struct Neurite {
     u: f64,
     /// Membrane potential/voltage.
     v: f64,
-    /// Total membrane potential of child compartment(s).
+    /// Total coupling input from child compartment(s).
     vcc: f64,
-    /// Membrane potential of parent compartment.
+    /// Coupling input from parent compartment.
     vpc: f64,
     /// Spike output.
     y: f64,
@@ -945,14 +3555,63 @@ pub struct Neurite {
     ext: Option<NeuriteExt>,
     /// Neurite's synapses.
     syn: Vec<Rc<RefCell<dyn Synapse>>>,
+    /// Electrodes injecting current directly into the neurite.
+    electrodes: Vec<Rc<RefCell<dyn Electrode>>>,
     /// Parent neurite.
     parent: Option<Rc<RefCell<Neurite>>>,
     /// Neurite's children.
-    child: Vec<Rc<RefCell<Neurite>>>
+    child: Vec<Rc<RefCell<Neurite>>>,
+    /// The integration method.
+    integrator: Integrator,
+    /// Spike time offset within the last time step.
+    ts: f64,
+    /// Parameters modulated by neural modulatory synapses.
+    modulated: Vec<ModulatedParameter>
+}
+
+// Debug for Neurite (the neurite tree, synapses and electrodes are not printed)
+impl std::fmt::Debug for Neurite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Neurite")
+            .field("id", &self.id)
+            .field("neurite_type", &self.neurite_type)
+            .field("spike_model", &self.spike_model)
+            .field("a", &self.a)
+            .field("b", &self.b)
+            .field("c", &self.c)
+            .field("d", &self.d)
+            .field("u", &self.u)
+            .field("v", &self.v)
+            .field("y", &self.y)
+            .field("children", &self.child.len())
+            .finish_non_exhaustive()
+    }
+}
+
+// PartialEq for Neurite (synapses, electrodes, parent and children are compared by identity)
+impl PartialEq for Neurite {
+    fn eq(&self, other: &Self) -> bool {
+        fn same<T: ?Sized>(a: &[Rc<RefCell<T>>], b: &[Rc<RefCell<T>>]) -> bool {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| Rc::ptr_eq(a, b))
+        }
+
+        self.id == other.id && self.neurite_type == other.neurite_type && self.spike_model == other.spike_model
+            && self.a == other.a && self.b == other.b && self.c == other.c && self.d == other.d && self.u == other.u
+            && self.v == other.v && self.vcc == other.vcc && self.vpc == other.vpc && self.y == other.y
+            && self.ext == other.ext && self.integrator == other.integrator && self.ts == other.ts
+            && self.modulated == other.modulated
+            && same(&self.syn, &other.syn) && same(&self.electrodes, &other.electrodes)
+            && same(&self.child, &other.child)
+            && match (&self.parent, &other.parent) {
+                (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+                (None, None) => true,
+                _ => false
+            }
+    }
 }
 
 /// Contains data for an extended neurite (neuron compartment).
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct NeuriteExt {
     /// Positive scalar value.
     k: f64,
@@ -991,81 +3650,48 @@ impl Neurite {
     /// Creates a new neurite with the specified spike model. If extended spike model is specified, the spike model defaults to a
     /// regular spiking model.
     pub fn new(neurite_type: NeuriteType, spike_model: SpikeModel) -> Self {
-        return match spike_model {
+        match spike_model {
             SpikeModel::Accommodation => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: 1.0,
                     c: -55.0,
                     d: 4.0,
                     u: -16.0,
                     v: -65.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::Bistability => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.1,
                     b: 0.26,
                     c: -60.0,
                     d: 0.0,
                     u: -15.86,
                     v: -61.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::ChatteringI | SpikeModel::TonicBursting => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: 0.2,
                     c: -50.0,
                     d: 2.0,
                     u: -14.0,
                     v: -70.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::ChatteringII => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.03,
                     b: 1.0,
                     c: -40.0,
                     d: 150.0,
                     u: 0.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 1.5,
                         gcc: 0.0,
@@ -1075,85 +3701,50 @@ impl Neurite {
                         vp: 25.0,
                         cap: 50.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::ClassI | SpikeModel::Integrator => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: -0.1,
                     c: -55.0,
                     d: 6.0,
                     u: 6.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::ClassII => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.2,
                     b: 0.26,
                     c: -65.0,
                     d: 0.0,
                     u: -16.64,
                     v: -64.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::DepolarizingAfterPotential => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 1.0,
                     b: 0.2,
                     c: -60.0,
                     d: -21.0,
                     u: -14.0,
                     v: -70.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::EntorhinalStellate => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.01,
                     b: 15.0,
                     c: -50.0,
                     d: 0.0,
                     u: 0.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 0.75,
                         gcc: 1.0,
@@ -1163,45 +3754,28 @@ impl Neurite {
                         vp: 30.0,
                         cap: 200.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::FastSpiking => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.1,
                     b: 0.2,
                     c: -65.0,
                     d: 2.0,
                     u: -14.0,
                     v: -70.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::FastSpikingBasket => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.2,
                     b: 0.0,
                     c: -55.0,
                     d: 0.0,
                     u: 0.0,
                     v: -55.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 1.0,
                         gcc: 0.5,
@@ -1211,25 +3785,17 @@ impl Neurite {
                         vp: 25.0,
                         cap: 20.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::HippocampalCA1PyramidalHighThresholdBursting => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: 0.5,
                     c: -45.0,
                     d: 50.0,
                     u: 0.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 1.0,
                         gcc: 1.0,
@@ -1239,25 +3805,17 @@ impl Neurite {
                         vp: 40.0,
                         cap: 50.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::HippocampalCA1PyramidalLowThresholdBurstingI => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: 0.5,
                     c: -40.0,
                     d: 55.0,
                     u: 0.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 1.0,
                         gcc: 1.0,
@@ -1267,25 +3825,17 @@ impl Neurite {
                         vp: 40.0,
                         cap: 50.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::HippocampalCA1PyramidalLowThresholdBurstingII => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: 0.5,
                     c: -35.0,
                     d: 60.0,
                     u: 0.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 1.0,
                         gcc: 1.0,
@@ -1295,25 +3845,17 @@ impl Neurite {
                         vp: 40.0,
                         cap: 50.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::HippocampalCA1PyramidalNonBursting => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: 0.5,
                     c: -50.0,
                     d: 50.0,
                     u: 0.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 1.0,
                         gcc: 1.0,
@@ -1323,65 +3865,39 @@ impl Neurite {
                         vp: 40.0,
                         cap: 50.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::InhibitionInducedBursting => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.026,
                     b: -1.0,
                     c: -45.0,
                     d: -2.0,
                     u: 63.8,
                     v: -63.8,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::InhibitionInducedSpiking => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: -1.0,
                     c: -60.0,
                     d: 8.0,
                     u: 63.8,
                     v: -63.8,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::IntrinsicallyBurstingPyramidal => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.01,
                     b: 5.0,
                     c: -56.0,
                     d: 130.0,
                     u: 0.0,
                     v: -75.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 1.2,
                         gcc: 1.0,
@@ -1391,25 +3907,17 @@ impl Neurite {
                         vp: 50.0,
                         cap: 150.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::IntrinsicallyBurstingPyramidalDendriteI => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 3.0,
                     b: 15.0,
                     c: -20.0,
                     d: 500.0,
                     u: 0.0,
                     v: -50.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 1.0,
                         gcc: 1.0,
@@ -1419,25 +3927,17 @@ impl Neurite {
                         vp: 20.0,
                         cap: 30.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::IntrinsicallyBurstingPyramidalDendriteII => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.01,
                     b: 5.0,
                     c: -35.0,
                     d: 1000.0,
                     u: 0.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 3.0,
                         gcc: 0.007,
@@ -1447,25 +3947,17 @@ impl Neurite {
                         vp: 10.0,
                         cap: 100.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::IntrinsicallyBurstingPyramidalSomaI => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.01,
                     b: 5.0,
                     c: -52.0,
                     d: 240.0,
                     u: 0.0,
                     v: -70.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 3.0,
                         gcc: 1.0,
@@ -1475,25 +3967,17 @@ impl Neurite {
                         vp: 50.0,
                         cap: 150.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::IntrinsicallyBurstingPyramidalSomaII => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.01,
                     b: 5.0,
                     c: -55.0,
                     d: 500.0,
                     u: 0.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 3.0,
                         gcc: 0.007,
@@ -1503,25 +3987,17 @@ impl Neurite {
                         vp: 50.0,
                         cap: 100.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::LatentSpikingNonBasket => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.17,
                     b: 5.0,
                     c: -45.0,
                     d: 20.0,
                     u: 0.0,
                     v: -53.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 0.3,
                         gcc: 0.6,
@@ -1531,25 +4007,17 @@ impl Neurite {
                         vp: 30.0,
                         cap: 20.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::LatentSpikingNonBasketDendrite => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.17,
                     b: 5.0,
                     c: -45.0,
                     d: 20.0,
                     u: 0.0,
                     v: -53.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 0.3,
                         gcc: 0.6,
@@ -1559,45 +4027,28 @@ impl Neurite {
                         vp: 100.0,
                         cap: 20.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::LowThresholdSpiking => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: 0.25,
                     c: -65.0,
                     d: 2.0,
                     u: -15.75,
                     v: -63.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::LowThresholdSpikingNonBasket => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.03,
                     b: 8.0,
                     c: -53.0,
                     d: 20.0,
                     u: 0.0,
                     v: -53.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 3.0,
                         gcc: 1.0,
@@ -1607,145 +4058,83 @@ impl Neurite {
                         vp: 40.0,
                         cap: 100.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::MixedMode => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: 0.2,
                     c: -55.0,
                     d: 4.0,
                     u: -14.0,
                     v: -70.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::PhasicBursting => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: 0.25,
                     c: -55.0,
                     d: 0.05,
                     u: -16.0,
                     v: -64.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::PhasicSpiking => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: 0.25,
                     c: -65.0,
                     d: 6.0,
                     u: -16.0,
                     v: -64.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::ReboundBurst => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.03,
                     b: 0.25,
                     c: -52.0,
                     d: 0.0,
                     u: -16.0,
                     v: -64.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::ReboundSpike | SpikeModel::ThresholdVariability => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.03,
                     b: 0.25,
                     c: -60.0,
                     d: 4.0,
                     u: -16.0,
                     v: -64.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::RegularSpiking => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: 0.2,
                     c: -65.0,
                     d: 8.0,
                     u: -12.6,
                     v: -63.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::RegularSpikingPyramidalI => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.03,
                     b: -2.0,
                     c: -50.0,
                     d: 100.0,
                     u: 0.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 0.7,
                         gcc: 1.0,
@@ -1755,25 +4144,17 @@ impl Neurite {
                         vp: 35.0,
                         cap: 100.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::RegularSpikingPyramidalII | SpikeModel::RegularSpikingSpinyStellate => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.01,
                     b: 5.0,
                     c: -60.0,
                     d: 400.0,
                     u: 0.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 3.0,
                         gcc: 3.0,
@@ -1783,25 +4164,17 @@ impl Neurite {
                         vp: 50.0,
                         cap: 100.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::RegularSpikingPyramidalL2L3Dendrite => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.01,
                     b: 5.0,
                     c: -55.0,
                     d: 400.0,
                     u: 0.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 3.0,
                         gcc: 3.0,
@@ -1811,25 +4184,17 @@ impl Neurite {
                         vp: 30.0,
                         cap: 100.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::RegularSpikingPyramidalL4Dendrite => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.01,
                     b: 5.0,
                     c: -50.0,
                     d: 400.0,
                     u: 0.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 3.0,
                         gcc: 3.0,
@@ -1839,25 +4204,17 @@ impl Neurite {
                         vp: 50.0,
                         cap: 100.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::RegularSpikingPyramidalL5L6Dendrite | SpikeModel::RegularSpikingSpinyStellateDendrite => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.01,
                     b: 5.0,
                     c: -50.0,
                     d: 400.0,
                     u: 0.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 3.0,
                         gcc: 3.0,
@@ -1867,65 +4224,39 @@ impl Neurite {
                         vp: 30.0,
                         cap: 100.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::ResonatorI => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.1,
                     b: 0.26,
                     c: -65.0,
                     d: 2.0,
                     u: -18.2,
                     v: -70.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::ResonatorII => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.1,
                     b: 0.26,
                     c: -60.0,
                     d: -1.0,
                     u: -16.12,
                     v: -62.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::ReticularThalamicNeuron => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.015,
                     b: 10.0,
                     c: -55.0,
                     d: 50.0,
                     u: 0.0,
                     v: 0.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 0.25,
                         gcc: 5.0,
@@ -1935,65 +4266,39 @@ impl Neurite {
                         vp: 0.0,
                         cap: 40.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::SpikeFrequencyAdaptation => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.01,
                     b: 0.2,
                     c: -65.0,
                     d: 8.0,
                     u: -14.0,
                     v: -70.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::SpikeLatency | SpikeModel::TonicSpiking => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: 0.2,
                     c: -65.0,
                     d: 6.0,
                     u: -14.0,
                     v: -70.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::SpinyProjection => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.01,
                     b: -20.0,
                     c: -55.0,
                     d: 150.0,
                     u: 0.0,
                     v: -80.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 1.0,
                         gcc: 1.0,
@@ -2003,45 +4308,28 @@ impl Neurite {
                         vp: 40.0,
                         cap: 50.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::SubthresholdOscillation => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.05,
                     b: 0.26,
                     c: -60.0,
                     d: 0.0,
                     u: -16.12,
                     v: -62.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::ThalamicInterneuron => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.05,
                     b: 7.0,
                     c: -65.0,
                     d: 50.0,
                     u: 0.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 0.5,
                         gcc: 5.0,
@@ -2051,25 +4339,17 @@ impl Neurite {
                         vp: 20.0,
                         cap: 20.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::Thalamocortical => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.1,
                     b: 15.0,
                     c: -60.0,
                     d: 10.0,
                     u: 0.0,
                     v: -60.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
                     ext: Some(NeuriteExt {
                         k: 1.6,
                         gcc: 2.0,
@@ -2079,54 +4359,61 @@ impl Neurite {
                         vp: 40.0,
                         cap: 200.0,
                     }),
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::ThalamocorticalSpiking => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: 0.25,
                     c: -65.0,
                     d: 0.05,
                     u: -15.75,
                     v: -63.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
             SpikeModel::ThalamocorticalBursting => {
                 Self {
-                    id: NeuriteID::new(),
-                    neurite_type,
-                    spike_model,
                     a: 0.02,
                     b: 0.25,
                     c: -65.0,
                     d: 0.05,
                     u: -21.75,
                     v: -87.0,
-                    vcc: 0.0,
-                    vpc: 0.0,
-                    y: 0.0,
-                    ext: None,
-                    syn: vec![],
-                    parent: None,
-                    child: vec![]
+                    ..Self::base(neurite_type, spike_model)
                 }
             }
         }
     }
 
+    /// Creates a new neurite of the specified type with the specified spike model, every parameter and variable 0, no
+    /// extended variables and no connections. The spike model presets start from this neurite.
+    fn base(neurite_type: NeuriteType, spike_model: SpikeModel) -> Self {
+        Self {
+            id: NeuriteID::new(),
+            neurite_type,
+            spike_model,
+            a: 0.0,
+            b: 0.0,
+            c: 0.0,
+            d: 0.0,
+            u: 0.0,
+            v: 0.0,
+            vcc: 0.0,
+            vpc: 0.0,
+            y: 0.0,
+            ext: None,
+            syn: vec![],
+            electrodes: vec![],
+            parent: None,
+            child: vec![],
+            integrator: Integrator::Euler,
+            ts: 0.0,
+            modulated: vec![]
+        }
+    }
+
     /// Sets the spike model. If the spike model is set to extended model, it will be defaulted to a regular spiking model.
     pub fn set_spike_model(&mut self, spike_model: SpikeModel) {
         match spike_model {
@@ -2888,7 +5175,7 @@ impl Neurite {
 
     /// Resets the neurites u, v, and y variables. If this is called on a neurite that has an extended spike model, an error message
     /// is returned.
-    fn reset(&mut self) {
+    pub fn reset(&mut self) {
         match self.spike_model {
             SpikeModel::Accommodation => {
                 self.u = -16.0;
@@ -3008,186 +5295,377 @@ impl Neurite {
         }
     }
 
-    /// Processes the neurite and returns the membrane potential and spike output values.
-    fn process(&mut self, time: f64) -> (f64, f64) {
-        match self.ext.as_deref() {
-            Some(e) => {
-                self.y = 0.0;
+    /// Returns the membrane potential derivative for the specified membrane potential, recovery variable and
+    /// synaptic and electrode input.
+    fn dv(&self, v: f64, u: f64, i: f64) -> f64 {
+        match &self.ext {
+            Some(e) => (e.k * (v - e.vr) * (v - e.vt) - u + i + self.vcc + self.vpc) / e.cap,
+            None => 0.04 * v * v + 5.0 * v + 140.0 - u + i + self.vcc + self.vpc
+        }
+    }
 
+    /// Returns the recovery variable derivative for the specified membrane potential and recovery variable.
+    fn du(&self, v: f64, u: f64) -> f64 {
+        match &self.ext {
+            Some(e) => {
                 match self.spike_model {
-                    SpikeModel::IntrinsicallyBurstingPyramidalDendriteI => {
-                        self.v += time * (e.k * (self.v - e.vr) * (self.v - e.vt) - e.vp * (self.vpc - self.v) - self.u + self.vcc) / e.cap;
+                    SpikeModel::FastSpikingBasket => {
+                        if v < e.vr {
+                            self.a * -u
+                        }
+                        else {
+                            self.a * ((0.025 * (v - e.vr)).powi(3) - u)
+                        }
                     }
-                    SpikeModel::IntrinsicallyBurstingPyramidalSomaI => {
-                        self.v += time * (e.k * (self.v - e.vr) * (self.v - e.vt) - e.vp * (self.vcc - self.v) - self.u + self.vpc) / e.cap;
+                    SpikeModel::ReticularThalamicNeuron => {
+                        let b = if v > -65.0 { 2.0 } else { 10.0 };
+                        self.a * (b * (v - e.vr) - u)
                     }
-                    _ => {
-                        self.v += time * (e.k * (self.v - e.vr) * (self.v - e.vt) - self.u + self.vcc + self.vpc) / e.cap;
+                    SpikeModel::Thalamocortical => {
+                        let b = if v > -65.0 { 0.0 } else { 15.0 };
+                        self.a * (b * (v - e.vr) - u)
                     }
+                    _ => self.a * (self.b * (v - e.vr) - u)
                 }
+            }
+            None => self.a * (self.b * v - u)
+        }
+    }
 
-                return match self.spike_model {
-                    SpikeModel::EntorhinalStellate => {
-                        self.u += time * self.a * (self.b * (self.v - e.vr) - self.u);
-
-                        if self.v > e.vp {
-                            self.v = self.c;
-                            self.y = 1.0;
-
-                            return (e.vp, self.y);
-                        }
+    /// Returns the spike peak membrane potential for the specified recovery variable.
+    fn peak(&self, u: f64) -> f64 {
+        match &self.ext {
+            Some(e) => {
+                match self.spike_model {
+                    SpikeModel::LowThresholdSpikingNonBasket => e.vp - 0.1 * u,
+                    SpikeModel::ThalamicInterneuron => e.vp - 0.08 * u,
+                    SpikeModel::Thalamocortical => e.vp + 0.1 * u,
+                    _ => e.vp
+                }
+            }
+            None => 30.0
+        }
+    }
 
-                        (self.v, self.y)
-                    }
-                    SpikeModel::FastSpikingBasket => {
+    /// Returns true if the specified membrane potential has reached the spike peak for the specified recovery variable.
+    /// Models with a recovery dependent peak and the basic models spike at the peak, the others only past it.
+    fn crossed(&self, v: f64, u: f64) -> bool {
+        match (&self.ext, self.spike_model) {
+            (None, _) | (Some(_), SpikeModel::LowThresholdSpikingNonBasket | SpikeModel::ThalamicInterneuron
+                                  | SpikeModel::Thalamocortical) => v >= self.peak(u),
+            _ => v > self.peak(u)
+        }
+    }
 
-                        if self.v < e.vr {
-                            self.u += time * self.a * -self.u;
-                        }
-                        else {
-                            self.u += time * self.a * (((0.025 * (self.v - e.vr)).pow(3.0)) - self.u);
-                        }
+    /// Resets the membrane potential and recovery variable after a spike.
+    fn spike_reset(&mut self) {
+        if self.ext.is_none() {
+            self.v = self.c;
+            self.u += self.d;
+            return;
+        }
 
-                        if self.v > e.vp {
-                            self.v = self.c;
-                            self.y = 1.0;
+        match self.spike_model {
+            SpikeModel::EntorhinalStellate | SpikeModel::FastSpikingBasket => {
+                self.v = self.c;
+            }
+            SpikeModel::LowThresholdSpikingNonBasket => {
+                self.v = self.c + 0.04 * self.u;
+                self.u = (self.u + self.d).min(670.0);
+            }
+            SpikeModel::ThalamicInterneuron => {
+                self.v = self.c + 0.08 * self.u;
+                self.u = (self.u + self.d).min(530.0);
+            }
+            SpikeModel::Thalamocortical => {
+                self.v = self.c - 0.1 * self.u;
+                self.u += self.d;
+            }
+            _ => {
+                self.v = self.c;
+                self.u += self.d;
+            }
+        }
+    }
 
-                            return (e.vp, self.y);
-                        }
+    /// Integrates the membrane potential and recovery variable from the specified state over the specified time step
+    /// (ms) and returns the new state. The adaptive integrator takes a single Dormand-Prince step.
+    fn integrate(&self, v: f64, u: f64, i: f64, h: f64) -> (f64, f64) {
+        match self.integrator {
+            Integrator::Euler => {
+                let v1 = v + h * self.dv(v, u, i);
+                (v1, u + h * self.du(v1, u))
+            }
+            Integrator::HalfStep => {
+                let v1 = v + 0.5 * h * self.dv(v, u, i);
+                let v2 = v1 + 0.5 * h * self.dv(v1, u, i);
+                (v2, u + h * self.du(v2, u))
+            }
+            Integrator::RK4 => {
+                let (k1v, k1u) = (self.dv(v, u, i), self.du(v, u));
+                let (v2, u2) = (v + 0.5 * h * k1v, u + 0.5 * h * k1u);
+                let (k2v, k2u) = (self.dv(v2, u2, i), self.du(v2, u2));
+                let (v3, u3) = (v + 0.5 * h * k2v, u + 0.5 * h * k2u);
+                let (k3v, k3u) = (self.dv(v3, u3, i), self.du(v3, u3));
+                let (v4, u4) = (v + h * k3v, u + h * k3u);
+                let (k4v, k4u) = (self.dv(v4, u4, i), self.du(v4, u4));
+
+                (v + h * (k1v + 2.0 * k2v + 2.0 * k3v + k4v) / 6.0, u + h * (k1u + 2.0 * k2u + 2.0 * k3u + k4u) / 6.0)
+            }
+            Integrator::RK45(_) => {
+                self.dormand_prince(v, u, i, h).0
+            }
+        }
+    }
 
-                        (self.v, self.y)
-                    }
-                    SpikeModel::IntrinsicallyBurstingPyramidalDendriteI => {
-                        self.u += time * self.a * (self.b * (self.v - e.vr) - self.u);
+    /// Takes a single Dormand-Prince 5(4) step from the specified state over the specified time step (ms) and returns
+    /// the fifth order state and the error estimate.
+    fn dormand_prince(&self, v: f64, u: f64, i: f64, h: f64) -> ((f64, f64), f64) {
+        const A: [[f64; 6]; 6] = [
+            [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+            [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+            [19372.0 / 6561.0, -25360.0 / 2187.0, 64448.0 / 6561.0, -212.0 / 729.0, 0.0, 0.0],
+            [9017.0 / 3168.0, -355.0 / 33.0, 46732.0 / 5247.0, 49.0 / 176.0, -5103.0 / 18656.0, 0.0],
+            [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0]
+        ];
+        const B5: [f64; 7] = [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0, 0.0];
+        const B4: [f64; 7] = [5179.0 / 57600.0, 0.0, 7571.0 / 16695.0, 393.0 / 640.0, -92097.0 / 339200.0, 187.0 / 2100.0,
+            1.0 / 40.0];
+
+        let mut kv = [0.0; 7];
+        let mut ku = [0.0; 7];
+        kv[0] = self.dv(v, u, i);
+        ku[0] = self.du(v, u);
+
+        for s in 1..7 {
+            let mut vs = v;
+            let mut us = u;
+
+            for j in 0..s {
+                vs += h * A[s - 1][j] * kv[j];
+                us += h * A[s - 1][j] * ku[j];
+            }
+
+            kv[s] = self.dv(vs, us, i);
+            ku[s] = self.du(vs, us);
+        }
 
-                        if self.v > e.vp {
-                            self.v = self.c;
-                            self.u += self.d;
-                            self.y = 1.0;
+        let mut v5 = v;
+        let mut u5 = u;
+        let mut ev = 0.0;
+        let mut eu = 0.0;
 
-                            return (e.vp, self.y);
-                        }
+        for s in 0..7 {
+            v5 += h * B5[s] * kv[s];
+            u5 += h * B5[s] * ku[s];
+            ev += h * (B5[s] - B4[s]) * kv[s];
+            eu += h * (B5[s] - B4[s]) * ku[s];
+        }
 
-                        (self.v, self.y)
-                    }
-                    SpikeModel::IntrinsicallyBurstingPyramidalSomaI => {
-                        self.u += time * self.a * (self.b * (self.v - e.vr) - self.u);
+        ((v5, u5), ev.abs().max(eu.abs()))
+    }
 
-                        if self.v > e.vp {
-                            self.v = self.c;
-                            self.u += self.d;
-                            self.y = 1.0;
+    /// Processes the neurite over the specified time step (ms) and returns the membrane potential and spike output
+    /// values. If the neurite spikes, the spike peak potential is returned and the time of the (first) threshold
+    /// crossing within the time step is stored as the spike offset.
+    pub fn process(&mut self, time: f64) -> (f64, f64) {
+        let i = self.synaptic_input() + self.electrode_input();
+        self.y = 0.0;
+        self.ts = 0.0;
+
+        // Modulators of the next time step start again from the parameter values before modulation
+        for m in &mut self.modulated {
+            m.stacked = false;
+        }
 
-                            return (e.vp, self.y);
-                        }
+        match self.integrator {
+            // Single step methods test the threshold after the step and interpolate the crossing time
+            Integrator::Euler | Integrator::HalfStep => {
+                let (v0, u0) = (self.v, self.u);
+                let (v, u) = self.integrate(v0, u0, i, time);
+                self.v = v;
+                self.u = u;
 
-                        (self.v, self.y)
-                    }
-                    SpikeModel::LowThresholdSpikingNonBasket => {
-                        self.u += time * self.a * (self.b * (self.v - e.vr) - self.u);
+                if self.crossed(v, u) {
+                    let peak = self.peak(u);
+                    let offset = time * (peak - v0) / (v - v0);
 
-                        let peak = e.vp - 0.1 * self.u;
+                    self.ts = if offset.is_finite() { offset.max(0.0).min(time) } else { time };
+                    self.spike_reset();
+                    self.y = 1.0;
 
-                        if self.v >= peak {
-                            self.v = self.c + 0.04 * self.u;
-                            self.u = (self.u + self.d).min(670.0);
-                            self.y = 1.0;
+                    return (peak, self.y);
+                }
 
-                            return (peak, self.y);
-                        }
+                (self.v, self.y)
+            }
+            // Runge-Kutta methods locate the threshold crossing inside the step and integrate the rest of the step
+            // after the reset
+            Integrator::RK4 | Integrator::RK45(_) => {
+                let h_min = time * 1.0e-6;
+                let mut t = 0.0;
+                let mut h = time;
+                let mut peak = None;
+
+                while time - t > h_min {
+                    h = h.min(time - t);
+
+                    let (v, u) = match self.integrator {
+                        Integrator::RK45(tol) => {
+                            let ((v, u), err) = self.dormand_prince(self.v, self.u, i, h);
+
+                            if (err > tol || err.is_nan()) && h > h_min {
+                                let scale = if err.is_finite() { 0.9 * (tol / err).powf(0.2) } else { 0.1 };
+                                h = (h * scale.clamp(0.1, 0.5)).max(h_min);
+                                continue;
+                            }
 
-                        (self.v, self.y)
-                    }
-                    SpikeModel::ReticularThalamicNeuron => {
-                        if self.v > -65.0 {
-                            self.b = 2.0;
-                        }
-                        else {
-                            self.b = 10.0;
+                            (v, u)
                         }
+                        _ => self.integrate(self.v, self.u, i, h)
+                    };
 
-                        self.u += time * self.a * (self.b * (self.v - e.vr) - self.u);
+                    if self.crossed(v, u) {
+                        // Bisect the step for the threshold crossing
+                        let mut lo = 0.0;
+                        let mut hi = h;
 
-                        if self.v > e.vp {
-                            self.v = self.c;
-                            self.u += self.d;
-                            self.y = 1.0;
+                        for _ in 0..48 {
+                            let mid = 0.5 * (lo + hi);
+                            let (vm, um) = self.integrate(self.v, self.u, i, mid);
 
-                            return (e.vp, self.y);
+                            if self.crossed(vm, um) {
+                                hi = mid;
+                            }
+                            else {
+                                lo = mid;
+                            }
                         }
 
-                        (self.v, self.y)
-                    }
-                    SpikeModel::ThalamicInterneuron => {
-                        self.u += time * self.a * (self.b * (self.v - e.vr) - self.u);
+                        let (vc, uc) = self.integrate(self.v, self.u, i, hi);
 
-                        let peak = e.vp - 0.08 * self.u;
+                        if self.y == 0.0 {
+                            self.ts = t + hi;
+                        }
 
-                        if self.v >= peak {
-                            self.v = self.c + 0.08 * self.u;
-                            self.u = (self.u + self.d).min(530.0);
-                            self.y = 1.0;
+                        self.v = vc;
+                        self.u = uc;
+                        peak = Some(self.peak(uc));
+                        self.spike_reset();
+                        self.y = 1.0;
+                        t += hi.max(h_min);
 
-                            return (peak, self.y);
+                        // A reset past the peak would spike again at once, so the rest of the step is skipped
+                        if self.crossed(self.v, self.u) {
+                            break;
                         }
-
-                        (self.v, self.y)
                     }
-                    SpikeModel::Thalamocortical => {
-                        if self.v > -65.0 {
-                            self.b = 0.0;
-                        }
-                        else {
-                            self.b = 15.0;
-                        }
+                    else {
+                        self.v = v;
+                        self.u = u;
+                        t += h;
 
-                        self.u += time * self.a * (self.b * (self.v - e.vr) - self.u);
+                        if let Integrator::RK45(_) = self.integrator {
+                            h *= 2.0;
+                        }
+                    }
+                }
 
-                        let peak = e.vp + 0.1 * self.u;
+                match peak {
+                    Some(peak) => (peak, self.y),
+                    None => (self.v, self.y)
+                }
+            }
+        }
+    }
 
-                        if self.v >= peak {
-                            self.v = self.c - 0.1 * self.u;
-                            self.u += self.d;
-                            self.y = 1.0;
+    /// Sets the integration method.
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
 
-                            return (peak, self.y);
-                        }
+    /// Returns the integration method.
+    pub fn integrator(&self) -> Integrator {
+        self.integrator
+    }
 
-                        (self.v, self.y)
-                    }
-                    _ => {
-                        self.u += time * self.a * (self.b * (self.v - e.vr) - self.u);
+    /// Returns the time of the first threshold crossing within the last time step (ms), measured from the start of
+    /// the time step. This is 0 if the neurite did not spike.
+    pub fn spike_offset(&self) -> f64 {
+        self.ts
+    }
 
-                        if self.v > e.vp {
-                            self.v = self.c;
-                            self.u += self.d;
-                            self.y = 1.0;
+    /// Returns the total input of the neurite's synapses. Modulatory synapses do not contribute any input.
+    fn synaptic_input(&self) -> f64 {
+        let mut i = 0.0;
 
-                            return (e.vp, self.y);
-                        }
+        for syn in &self.syn {
+            let syn = syn.borrow();
 
-                        (self.v, self.y)
-                    }
+            match syn.syn_type() {
+                SynapseType::SynapticModulator(_) | SynapseType::NeuralModulator(_) => {}
+                _ => {
+                    i += syn.input_to(self.id);
                 }
             }
-            None() => {
-                self.y = 0.0;
-                self.v += time * (0.04 * self.v * self.v + 5.0 * self.v + 140.0 - self.u + self.vcc + self.vcc);
-                self.u += time * self.a * (self.b * self.v - self.u);
+        }
 
-                if self.v >= 30.0 {
-                    self.v = self.c;
-                    self.u += self.d;
-                    self.y = 1.0;
+        i
+    }
 
-                    return (30.0, self.y);
-                }
+    /// Returns the total current injected by the neurite's electrodes.
+    fn electrode_input(&self) -> f64 {
+        self.electrodes.iter().map(|e| e.borrow().output()).sum()
+    }
 
-                (self.v, self.y)
+    /// Attaches the specified electrode to the neurite. The electrode's output is injected directly into the neurite as
+    /// current.
+    pub fn attach_electrode(&mut self, electrode: Rc<RefCell<dyn Electrode>>) {
+        self.electrodes.push(electrode);
+    }
+
+    /// Detaches the electrode at the specified index. Returns false if index is out of bounds.
+    pub fn detach_electrode(&mut self, index: usize) -> bool {
+        if index >= self.electrodes.len() {
+            return false;
+        }
+
+        self.electrodes.remove(index);
+
+        true
+    }
+
+    /// Attaches the specified synapse to the neurite. If a synapse with the same synaptic ID is already attached, this
+    /// returns an error message.
+    pub fn attach_synapse(&mut self, syn: Rc<RefCell<dyn Synapse>>) -> Result<(), &'static str> {
+        let id = syn.borrow().syn_id();
+
+        if self.syn.iter().any(|s| s.borrow().syn_id() == id) {
+            return Err("Cannot attach the same synapse to a neurite more than once.");
+        }
+
+        self.syn.push(syn);
+
+        Ok(())
+    }
+
+    /// Detaches the synapse with the specified synaptic ID. Returns false if no such synapse is attached.
+    pub fn detach_synapse(&mut self, id: SynapticID) -> bool {
+        match self.syn.iter().position(|s| s.borrow().syn_id() == id) {
+            Some(index) => {
+                self.syn.remove(index);
+                true
             }
+            None => false
         }
     }
 
+    /// Returns the neurite's synapses.
+    pub fn synapses(&self) -> Vec<Rc<RefCell<dyn Synapse>>> {
+        self.syn.clone()
+    }
+
     /// Adds the specified child neurite. If the child cannot be added, this returns an error message specifying why.
     pub fn add_child(&mut self, child: Rc<RefCell<Neurite>>) -> Result<(), &'static str> {
         match self.neurite_type {
@@ -3389,9 +5867,11 @@ impl Neurite {
             return false;
         }
 
-        for i in 0..self.child[index].borrow().child.len() {
-            self.child[index].clone().borrow_mut().child[i].borrow_mut().parent = Some(self.child[index].borrow().clone().parent.unwrap().clone());
-            self.child.push(self.child[index].clone().borrow().child[i].clone());
+        let removed = Rc::clone(&self.child[index]);
+
+        for i in 0..removed.borrow().child.len() {
+            removed.borrow().child[i].borrow_mut().parent = removed.borrow().parent.clone();
+            self.child.push(Rc::clone(&removed.borrow().child[i]));
         }
 
         self.child.remove(index);
@@ -3467,4 +5947,429 @@ impl Neurite {
 
         Ok(())
     }
-}
\ No newline at end of file
+    /// Returns the value of the specified modulatable variable. Extended variables of a neurite without an extended
+    /// spike model are 0.
+    pub fn mod_var(&self, var: NeuriteModVar) -> f64 {
+        match var {
+            NeuriteModVar::A => self.a,
+            NeuriteModVar::B => self.b,
+            NeuriteModVar::C => self.c,
+            NeuriteModVar::D => self.d,
+            NeuriteModVar::U => self.u,
+            NeuriteModVar::V => self.v,
+            NeuriteModVar::GCC => self.ext.as_ref().map_or(0.0, |e| e.gcc),
+            NeuriteModVar::GPC => self.ext.as_ref().map_or(0.0, |e| e.gpc),
+            NeuriteModVar::VR => self.ext.as_ref().map_or(0.0, |e| e.vr),
+            NeuriteModVar::VT => self.ext.as_ref().map_or(0.0, |e| e.vt),
+            NeuriteModVar::CAP => self.ext.as_ref().map_or(0.0, |e| e.cap)
+        }
+    }
+
+    /// Modulates the specified variable by the specified modulation and level over the specified time step (ms). The
+    /// state variables u and v are driven at a rate per ms. Parameters are modulated from their value before the first
+    /// modulation, and the modulations of a parameter by several synapses in the same time step are stacked.
+    pub fn modulate(&mut self, var: NeuriteModVar, modulation: &Modulation, level: f64, time: f64) {
+        let value = self.mod_var(var);
+
+        let value = match var {
+            NeuriteModVar::U | NeuriteModVar::V => modulation.drive(value, level, time),
+            _ => {
+                let base = match self.modulated.iter_mut().find(|m| m.var == var) {
+                    Some(m) if m.stacked => value,
+                    Some(m) => {
+                        m.stacked = true;
+                        m.base
+                    }
+                    None => {
+                        self.modulated.push(ModulatedParameter { var, base: value, stacked: true });
+                        value
+                    }
+                };
+
+                modulation.apply(base, level)
+            }
+        };
+
+        self.set_mod_var(var, value);
+    }
+
+    /// Restores the specified parameter to its value before it was first modulated.
+    pub fn unmodulate(&mut self, var: NeuriteModVar) {
+        if let Some(index) = self.modulated.iter().position(|m| m.var == var) {
+            let m = self.modulated.remove(index);
+            self.set_mod_var(var, m.base);
+        }
+    }
+
+    /// Sets the value of the specified modulatable variable. Extended variables are ignored on a neurite without an
+    /// extended spike model.
+    pub fn set_mod_var(&mut self, var: NeuriteModVar, value: f64) {
+        match var {
+            NeuriteModVar::A => self.a = value,
+            NeuriteModVar::B => self.b = value,
+            NeuriteModVar::C => self.c = value,
+            NeuriteModVar::D => self.d = value,
+            NeuriteModVar::U => self.u = value,
+            NeuriteModVar::V => self.v = value,
+            _ => {
+                if let Some(e) = self.ext.as_mut() {
+                    match var {
+                        NeuriteModVar::GCC => e.gcc = value,
+                        NeuriteModVar::GPC => e.gpc = value,
+                        NeuriteModVar::VR => e.vr = value,
+                        NeuriteModVar::VT => e.vt = value,
+                        NeuriteModVar::CAP => e.cap = value,
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the current value of the specified variable.
+    pub fn probe(&self, var: NeuriteVar) -> f64 {
+        match var {
+            NeuriteVar::U => self.u,
+            NeuriteVar::V => self.v,
+            NeuriteVar::Y => self.y,
+            NeuriteVar::VCC => self.vcc,
+            NeuriteVar::VPC => self.vpc
+        }
+    }
+
+    /// Creates a new neurite from the specified model file record, without its children, electrodes or synapses.
+    pub fn from_record(record: &NeuriteRecord) -> Self {
+        let mut neurite = Self::new(record.neurite_type, record.spike_model);
+
+        neurite.id = record.id;
+        neurite.a = record.a;
+        neurite.b = record.b;
+        neurite.c = record.c;
+        neurite.d = record.d;
+        neurite.u = record.u;
+        neurite.v = record.v;
+        neurite.ext = record.ext.clone();
+        neurite.integrator = record.integrator;
+
+        neurite
+    }
+
+    /// Returns the dynamic state of the neurite.
+    pub fn state(&self) -> NeuriteState {
+        NeuriteState {
+            id: self.id,
+            a: self.a,
+            b: self.b,
+            c: self.c,
+            d: self.d,
+            u: self.u,
+            v: self.v,
+            vcc: self.vcc,
+            vpc: self.vpc,
+            y: self.y,
+            ts: self.ts,
+            ext: self.ext.clone(),
+            modulated: self.modulated.clone()
+        }
+    }
+
+    /// Restores the dynamic state of the neurite. Children, electrodes and synapses are not changed.
+    pub fn restore(&mut self, state: &NeuriteState) {
+        self.a = state.a;
+        self.b = state.b;
+        self.c = state.c;
+        self.d = state.d;
+        self.u = state.u;
+        self.v = state.v;
+        self.vcc = state.vcc;
+        self.vpc = state.vpc;
+        self.y = state.y;
+        self.ts = state.ts;
+        self.ext = state.ext.clone();
+        self.modulated = state.modulated.clone();
+    }
+
+    /// Returns the model file record of the neurite. Parameters modulated by neural modulatory synapses are saved with
+    /// their values before modulation.
+    pub fn record(&self, refs: &mut ModelRefs) -> NeuriteRecord {
+        let base = |var: NeuriteModVar| self.modulated.iter().find(|m| m.var == var).map_or(self.mod_var(var), |m| m.base);
+
+        NeuriteRecord {
+            id: self.id,
+            neurite_type: self.neurite_type,
+            spike_model: self.spike_model,
+            a: base(NeuriteModVar::A),
+            b: base(NeuriteModVar::B),
+            c: base(NeuriteModVar::C),
+            d: base(NeuriteModVar::D),
+            u: self.u,
+            v: self.v,
+            ext: self.ext.clone().map(|mut e| {
+                e.gcc = base(NeuriteModVar::GCC);
+                e.gpc = base(NeuriteModVar::GPC);
+                e.vr = base(NeuriteModVar::VR);
+                e.vt = base(NeuriteModVar::VT);
+                e.cap = base(NeuriteModVar::CAP);
+                e
+            }),
+            integrator: self.integrator,
+            children: self.child.iter().map(|c| refs.neurite(c)).collect(),
+            electrodes: self.electrodes.iter().map(|e| refs.electrode(e)).collect(),
+            synapses: self.syn.iter().map(|s| refs.synapse(s)).collect()
+        }
+    }
+
+    /// Returns the names and values of the neurite's model parameters, including extended parameters of a neurite
+    /// with an extended spike model.
+    pub fn parameters(&self) -> Vec<(&'static str, f64)> {
+        let mut params = vec![("a", self.a), ("b", self.b), ("c", self.c), ("d", self.d)];
+
+        if let Some(e) = self.ext.as_ref() {
+            params.extend([("k", e.k), ("gcc", e.gcc), ("gpc", e.gpc), ("vr", e.vr), ("vt", e.vt), ("vp", e.vp),
+                ("cap", e.cap)]);
+        }
+
+        params
+    }
+
+    /// Returns the neurite ID.
+    pub fn id(&self) -> NeuriteID {
+        self.id
+    }
+
+    /// Returns the neurite type.
+    pub fn neurite_type(&self) -> NeuriteType {
+        self.neurite_type
+    }
+
+    /// Returns the spike model.
+    pub fn spike_model(&self) -> SpikeModel {
+        self.spike_model
+    }
+
+    /// Returns the membrane potential/voltage.
+    pub fn v(&self) -> f64 {
+        self.v
+    }
+
+    /// Returns the spike output.
+    pub fn y(&self) -> f64 {
+        self.y
+    }
+
+    /// Returns the neurite's children.
+    pub fn children(&self) -> Vec<Rc<RefCell<Neurite>>> {
+        self.child.clone()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a neuron (a neurite tree rooted at a soma neurite).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Neuron {
+    /// The soma neurite (root of the neurite tree).
+    soma: Rc<RefCell<Neurite>>
+}
+
+// Neuron functions
+impl Neuron {
+    /// Creates a new neuron with a soma neurite using the specified spike model.
+    pub fn new(spike_model: SpikeModel) -> Self {
+        Self {
+            soma: Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, spike_model)))
+        }
+    }
+
+    /// Creates a new neuron from the specified soma neurite and its children. If the neurite is not a soma neurite,
+    /// this returns an error message.
+    pub fn from_soma(soma: Rc<RefCell<Neurite>>) -> Result<Self, &'static str> {
+        if soma.borrow().neurite_type != NeuriteType::Soma {
+            return Err("A neuron must be rooted at a soma neurite.");
+        }
+
+        Ok(Self {
+            soma
+        })
+    }
+
+    /// Returns the soma neurite.
+    pub fn soma(&self) -> Rc<RefCell<Neurite>> {
+        Rc::clone(&self.soma)
+    }
+
+    /// Returns every neurite of the neuron, starting with the soma. Children always follow their parent.
+    pub fn neurites(&self) -> Vec<Rc<RefCell<Neurite>>> {
+        self.tree().into_iter().map(|(n, _)| n).collect()
+    }
+
+    /// Returns every neurite of the neuron along with the index of its parent neurite (none for the soma).
+    fn tree(&self) -> Vec<(Rc<RefCell<Neurite>>, Option<usize>)> {
+        let mut tree: Vec<(Rc<RefCell<Neurite>>, Option<usize>)> = vec![(Rc::clone(&self.soma), None)];
+        let mut i = 0;
+
+        while i < tree.len() {
+            let children = tree[i].0.borrow().child.clone();
+
+            for child in children {
+                tree.push((child, Some(i)));
+            }
+
+            i += 1;
+        }
+
+        tree
+    }
+
+    /// Returns the conduction delay (ms) from the soma to the specified neurite, given the conduction delay of a single
+    /// axon neurite. Only axon neurites on the path from the soma contribute to the delay. Returns none if the neurite
+    /// is not part of the neuron.
+    pub fn axon_delay(&self, neurite: &Rc<RefCell<Neurite>>, delay: f64) -> Option<f64> {
+        let tree = self.tree();
+        let mut index = tree.iter().position(|(n, _)| Rc::ptr_eq(n, neurite))?;
+        let mut count = 0;
+
+        loop {
+            if tree[index].0.borrow().neurite_type == NeuriteType::Axon {
+                count += 1;
+            }
+
+            match tree[index].1 {
+                Some(parent) => index = parent,
+                None => break
+            }
+        }
+
+        Some(count as f64 * delay)
+    }
+
+    /// Sets the integration method of every neurite.
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        for n in self.neurites() {
+            n.borrow_mut().set_integrator(integrator);
+        }
+    }
+
+    /// Resets the u, v, and y variables of every neurite.
+    pub fn reset(&mut self) {
+        for n in self.neurites() {
+            let mut n = n.borrow_mut();
+            n.reset();
+            n.vcc = 0.0;
+            n.vpc = 0.0;
+        }
+    }
+
+    /// Processes every neurite once over the specified time step (ms) and returns the neurite ID, membrane potential
+    /// and spike output of each neurite. The coupling input of each neurite is computed from the membrane potentials at
+    /// the start of the time step, so the result does not depend on the order the neurites are processed in.
+    pub fn process(&mut self, time: f64) -> Vec<(NeuriteID, f64, f64)> {
+        let tree = self.tree();
+        let v: Vec<f64> = tree.iter().map(|(n, _)| n.borrow().v).collect();
+        let mut vcc: Vec<f64> = vec![0.0; tree.len()];
+        let mut vpc: Vec<f64> = vec![0.0; tree.len()];
+
+        for (i, (n, parent)) in tree.iter().enumerate() {
+            if let Some(p) = *parent {
+                // Parent compartment input into the child
+                if let Some(e) = &n.borrow().ext {
+                    vpc[i] = e.gpc * (v[p] - v[i]);
+                }
+
+                // Child compartment input into the parent
+                if let Some(e) = &tree[p].0.borrow().ext {
+                    vcc[p] += e.gcc * (v[i] - v[p]);
+                }
+            }
+        }
+
+        let mut out: Vec<(NeuriteID, f64, f64)> = Vec::with_capacity(tree.len());
+
+        for (i, (n, _)) in tree.iter().enumerate() {
+            let mut n = n.borrow_mut();
+            n.vcc = vcc[i];
+            n.vpc = vpc[i];
+
+            let (v, y) = n.process(time);
+            out.push((n.id, v, y));
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the membrane potentials of the soma and dendrite of an unstimulated two compartment neuron of the
+    /// specified spike models after one second, and whether any compartment spiked.
+    fn rest(soma: SpikeModel, dendrite: SpikeModel) -> (f64, f64, bool) {
+        let mut neuron = Neuron::new(soma);
+        let child = Rc::new(RefCell::new(Neurite::new(NeuriteType::ApicalTrunk, dendrite)));
+        let mut spiked = false;
+
+        neuron.soma().borrow_mut().add_child(child.clone()).unwrap();
+
+        for _ in 0..10000 {
+            spiked |= neuron.process(0.1).iter().any(|(_, _, y)| *y > 0.0);
+        }
+
+        let v = neuron.soma().borrow().v;
+        let vd = child.borrow().v;
+        (v, vd, spiked)
+    }
+
+    #[test]
+    fn two_compartment_neurons_rest_near_their_resting_potentials() {
+        let (v, vd, spiked) = rest(SpikeModel::IntrinsicallyBurstingPyramidalSomaI,
+                                   SpikeModel::IntrinsicallyBurstingPyramidalDendriteI);
+
+        assert!(!spiked);
+        assert!((v + 70.0).abs() < 1.0 && (vd + 50.0).abs() < 2.0, "{} {}", v, vd);
+
+        let (v, vd, spiked) = rest(SpikeModel::IntrinsicallyBurstingPyramidalSomaII,
+                                   SpikeModel::IntrinsicallyBurstingPyramidalDendriteII);
+
+        assert!(!spiked);
+        assert!((v + 60.0).abs() < 1.0e-9 && (vd + 60.0).abs() < 1.0e-9, "{} {}", v, vd);
+    }
+
+    /// Returns the facilitation and depression scalars of the specified short-term plasticity data after a spike and
+    /// the specified number of time steps (ms) without spikes.
+    fn relax(mut stp: ShortTermPlasticity, time: f64, steps: usize) -> (f64, f64) {
+        stp.learn(true, time);
+
+        for _ in 0..steps {
+            stp.learn(false, time);
+        }
+
+        (stp.f, stp.d)
+    }
+
+    #[test]
+    fn short_term_plasticity_relaxes_with_time_constants_in_ms() {
+        let (f, d) = relax(ShortTermPlasticity::new(50.0, 10.0, 0.5), 0.1, 500);
+
+        // 50 ms is one facilitation recovery time and five depression decay times
+        assert!((f - (-1.0f64).exp()).abs() < 1.0e-12, "{}", f);
+        assert!((d - (1.0 - (-5.0f64).exp())).abs() < 1.0e-12, "{}", d);
+
+        let fine = relax(ShortTermPlasticity::new(50.0, 10.0, 0.5), 0.01, 2000);
+        let coarse = relax(ShortTermPlasticity::new(50.0, 10.0, 0.5), 1.0, 20);
+
+        assert!((fine.0 - coarse.0).abs() < 1.0e-12 && (fine.1 - coarse.1).abs() < 1.0e-12, "{:?} {:?}", fine, coarse);
+    }
+
+    #[test]
+    fn pair_stdp_keeps_no_slow_traces() {
+        let mut stdp = SpikeTimingDependentPlasticity::pair(0.01, 0.012, 20.0, 20.0, 0.0, 1.0);
+
+        for t in 0..1000 {
+            stdp.dw(t % 3 == 0, t % 5 == 0, 1.0);
+        }
+
+        assert!(stdp.r1 > 0.0 && stdp.o1 > 0.0);
+        assert_eq!((stdp.r2, stdp.o2), (0.0, 0.0));
+    }
+}
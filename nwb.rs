@@ -0,0 +1,281 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use hdf5::types::VarLenUnicode;
+use hdf5::{Group, Location, ObjectReference1};
+use uuid::Uuid;
+use crate::neural::{NeuriteID, NeuriteVar};
+use crate::recording::{ElectrodeVar, Probe, ProbeSource, ProbeVar, SpikeRecorder};
+
+/// The NWB format version written.
+const NWB_VERSION: &str = "2.5.0";
+
+/// Contains data for a uniformly sampled series of a neurite.
+#[derive(Clone, Debug, PartialEq)]
+struct NwbSeries {
+    /// The neurite ID of the recorded (or stimulated) neurite.
+    neurite: NeuriteID,
+    /// The time of the first sample (ms).
+    start: f64,
+    /// The sampling rate (Hz).
+    rate: f64,
+    /// The samples.
+    data: Vec<f64>
+}
+
+/// Contains data for a simulated session exported as an NWB 2.x file. Membrane voltages are written as
+/// CurrentClampSeries, electrode currents as CurrentClampStimulusSeries, and spike times as a Units table. Every series
+/// and unit is keyed by the neurite ID of its neurite, which also names the neurite's intracellular electrode.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NwbSession {
+    /// The session description.
+    description: String,
+    /// The session start time (ISO 8601).
+    start_time: String,
+    /// The recorded membrane voltages (mV).
+    voltages: Vec<NwbSeries>,
+    /// The stimulus electrode currents (pA).
+    stimuli: Vec<NwbSeries>,
+    /// The recorded spike times (ms) of each neurite.
+    units: Vec<(NeuriteID, Vec<f64>)>
+}
+
+// NwbSession functions
+impl NwbSession {
+    /// Creates a new empty session with the specified description and start time (ISO 8601, such as
+    /// "2024-01-01T00:00:00Z").
+    pub fn new(description: &str, start_time: &str) -> Self {
+        Self {
+            description: description.to_string(),
+            start_time: start_time.to_string(),
+            voltages: vec![],
+            stimuli: vec![],
+            units: vec![]
+        }
+    }
+
+    /// Adds the membrane voltage trace of the specified probe, which must record the voltage of a neurite.
+    pub fn add_voltage(&mut self, probe: &Probe, time_inc: f64) -> Result<(), &'static str> {
+        let neurite = match probe.source() {
+            ProbeSource::Neurite(n) => n.borrow().id(),
+            _ => return Err("Probe does not record a neurite.")
+        };
+
+        self.voltages.push(Self::series(probe, ProbeVar::Neurite(NeuriteVar::V), neurite, time_inc)?);
+        Ok(())
+    }
+
+    /// Adds the stimulus current trace of the specified probe, which must record the output of an electrode
+    /// injecting current into the specified neurite.
+    pub fn add_stimulus(&mut self, probe: &Probe, neurite: NeuriteID, time_inc: f64) -> Result<(), &'static str> {
+        if !matches!(probe.source(), ProbeSource::Electrode(_)) {
+            return Err("Probe does not record an electrode.");
+        }
+
+        self.stimuli.push(Self::series(probe, ProbeVar::Electrode(ElectrodeVar::Output), neurite, time_inc)?);
+        Ok(())
+    }
+
+    /// Adds the spike times of every neurite of the specified spike recorder as units.
+    pub fn add_units(&mut self, recorder: &SpikeRecorder) {
+        for n in recorder.neurites() {
+            let id = n.borrow().id();
+            self.units.push((id, recorder.spike_times(id)));
+        }
+    }
+
+    /// Returns the series of the specified variable of the specified probe. The sampling rate is taken from the sample
+    /// times, or from the time step (ms) if there are fewer than two samples.
+    fn series(probe: &Probe, var: ProbeVar, neurite: NeuriteID, time_inc: f64) -> Result<NwbSeries, &'static str> {
+        let data = probe.trace(var).ok_or("Probe does not record the variable.")?;
+        let times = probe.times();
+        let interval = if times.len() > 1 { times[1] - times[0] } else { time_inc };
+
+        Ok(NwbSeries {
+            neurite,
+            start: times.front().copied().unwrap_or(0.0),
+            rate: 1000.0 / interval,
+            data
+        })
+    }
+
+    /// Writes the session to an NWB 2.x (HDF5) file.
+    pub fn write(&self, path: &str) -> Result<(), String> {
+        self.write_file(path).map_err(|e| format!("Cannot write NWB file {}: {}", path, e))
+    }
+
+    /// Writes the session to an NWB 2.x (HDF5) file.
+    fn write_file(&self, path: &str) -> hdf5::Result<()> {
+        let file = hdf5::File::create(path)?;
+
+        set_type(&file, "NWBFile")?;
+        set_str_attr(&file, "nwb_version", NWB_VERSION)?;
+        set_str(&file, "identifier", &new_id())?;
+        set_str(&file, "session_description", &self.description)?;
+        set_str(&file, "session_start_time", &self.start_time)?;
+        set_str(&file, "timestamps_reference_time", &self.start_time)?;
+        file.new_dataset_builder().with_data(&[str_value(&utc_now())?][..]).create("file_create_date")?;
+
+        let acquisition = file.create_group("acquisition")?;
+        let stimulus = file.create_group("stimulus")?;
+        let presentation = stimulus.create_group("presentation")?;
+        stimulus.create_group("templates")?;
+        file.create_group("analysis")?;
+        file.create_group("processing")?;
+
+        let general = file.create_group("general")?;
+        let devices = general.create_group("devices")?;
+        let device = devices.create_group("simulation")?;
+        set_type(&device, "Device")?;
+        set_str_attr(&device, "description", "Simulated neurite compartments")?;
+
+        // One intracellular electrode per neurite with a series
+        let ephys = general.create_group("intracellular_ephys")?;
+        let mut electrodes: Vec<NeuriteID> = vec![];
+
+        for s in self.voltages.iter().chain(&self.stimuli) {
+            if !electrodes.contains(&s.neurite) {
+                let electrode = ephys.create_group(&electrode_name(s.neurite))?;
+                set_type(&electrode, "IntracellularElectrode")?;
+                set_str(&electrode, "description", &format!("Neurite {}", s.neurite.uuid()))?;
+                electrode.link_soft("/general/devices/simulation", "device")?;
+                electrodes.push(s.neurite);
+            }
+        }
+
+        // Membrane voltages are in mV and electrode currents in pA
+        for s in &self.voltages {
+            let group = write_series(&acquisition, s, "voltage", "CurrentClampSeries", "volts", 1e-3)?;
+
+            for name in ["bias_current", "bridge_balance", "capacitance_compensation"] {
+                group.new_dataset::<f64>().shape(()).create(name)?.write_scalar(&0.0)?;
+            }
+        }
+
+        for s in &self.stimuli {
+            write_series(&presentation, s, "stimulus", "CurrentClampStimulusSeries", "amperes", 1e-12)?;
+        }
+
+        self.write_units(&file)
+    }
+
+    /// Writes the units table (spike times in seconds, keyed by neurite ID).
+    fn write_units(&self, file: &hdf5::File) -> hdf5::Result<()> {
+        let units = file.create_group("units")?;
+        set_type(&units, "Units")?;
+        set_str_attr(&units, "description", "Spike times of simulated neurites")?;
+
+        let colnames = [str_value("spike_times")?, str_value("neurite_id")?];
+        units.new_attr_builder().with_data(&colnames[..]).create("colnames")?;
+
+        let ids: Vec<i64> = (0..self.units.len() as i64).collect();
+        let neurite_ids: Vec<VarLenUnicode> = self.units.iter()
+            .map(|(id, _)| str_value(&id.uuid().to_string()))
+            .collect::<hdf5::Result<_>>()?;
+        let spike_times: Vec<f64> = self.units.iter().flat_map(|(_, t)| t.iter().map(|t| t / 1000.0)).collect();
+        let index: Vec<u64> = self.units.iter()
+            .scan(0u64, |end, (_, t)| {
+                *end += t.len() as u64;
+                Some(*end)
+            })
+            .collect();
+
+        let id = units.new_dataset_builder().with_data(ids.as_slice()).create("id")?;
+        set_type(&id, "ElementIdentifiers")?;
+
+        let times = units.new_dataset_builder().with_data(spike_times.as_slice()).create("spike_times")?;
+        set_type(&times, "VectorData")?;
+        set_str_attr(&times, "description", "Spike times (s)")?;
+
+        let times_index = units.new_dataset_builder().with_data(index.as_slice()).create("spike_times_index")?;
+        set_type(&times_index, "VectorIndex")?;
+        set_str_attr(&times_index, "description", "Index into the spike times")?;
+        times_index.new_attr::<ObjectReference1>().create("target")?
+            .write_scalar(&units.reference::<ObjectReference1>("spike_times")?)?;
+
+        let neurite = units.new_dataset_builder().with_data(neurite_ids.as_slice()).create("neurite_id")?;
+        set_type(&neurite, "VectorData")?;
+        set_str_attr(&neurite, "description", "Neurite ID")?;
+
+        Ok(())
+    }
+}
+
+/// Writes the specified series to a new group of the specified NWB type in the specified parent group, with data in
+/// the specified unit after multiplying by the specified conversion, and returns the group.
+fn write_series(parent: &Group, s: &NwbSeries, prefix: &str, neurodata_type: &str, unit: &str, conversion: f64)
+    -> hdf5::Result<Group> {
+    let group = parent.create_group(&format!("{}_{}", prefix, s.neurite.uuid()))?;
+    set_type(&group, neurodata_type)?;
+    set_str_attr(&group, "description", &format!("Neurite {}", s.neurite.uuid()))?;
+    set_str_attr(&group, "comments", "no comments")?;
+    set_str_attr(&group, "stimulus_description", "N/A")?;
+
+    let data = group.new_dataset_builder().with_data(s.data.as_slice()).create("data")?;
+    set_str_attr(&data, "unit", unit)?;
+    data.new_attr::<f64>().create("conversion")?.write_scalar(&conversion)?;
+    data.new_attr::<f64>().create("resolution")?.write_scalar(&-1.0)?;
+    data.new_attr::<f64>().create("offset")?.write_scalar(&0.0)?;
+
+    let start = group.new_dataset::<f64>().shape(()).create("starting_time")?;
+    start.write_scalar(&(s.start / 1000.0))?;
+    start.new_attr::<f64>().create("rate")?.write_scalar(&s.rate)?;
+    set_str_attr(&start, "unit", "seconds")?;
+
+    group.link_soft(&format!("/general/intracellular_ephys/{}", electrode_name(s.neurite)), "electrode")?;
+
+    Ok(group)
+}
+
+/// Returns the intracellular electrode name of the specified neurite.
+fn electrode_name(neurite: NeuriteID) -> String {
+    format!("electrode_{}", neurite.uuid())
+}
+
+/// Returns the current UTC time in ISO 8601 format (such as "2024-01-01T00:00:00Z").
+fn utc_now() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, t) = (secs / 86400, secs % 86400);
+
+    // Civil date of the day count since 1970-01-01, in 400 year eras starting on March 1st
+    let z = days + 719468;
+    let doe = z % 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = z / 146097 * 400 + yoe + u64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, t / 3600, t / 60 % 60, t % 60)
+}
+
+/// Returns a new unique identifier.
+fn new_id() -> String {
+    Uuid::now_v7().to_string()
+}
+
+/// Returns the specified string as an HDF5 string value.
+fn str_value(value: &str) -> hdf5::Result<VarLenUnicode> {
+    value.parse().map_err(|e| hdf5::Error::from(format!("{}", e)))
+}
+
+/// Sets the NWB type attributes (namespace, neurodata type and object ID) of the specified location.
+fn set_type(loc: &Location, neurodata_type: &str) -> hdf5::Result<()> {
+    set_str_attr(loc, "namespace", if neurodata_type.starts_with("Vector") || neurodata_type == "ElementIdentifiers" {
+        "hdmf-common"
+    }
+    else {
+        "core"
+    })?;
+    set_str_attr(loc, "neurodata_type", neurodata_type)?;
+    set_str_attr(loc, "object_id", &new_id())
+}
+
+/// Sets the specified string attribute of the specified location.
+fn set_str_attr(loc: &Location, name: &str, value: &str) -> hdf5::Result<()> {
+    loc.new_attr::<VarLenUnicode>().create(name)?.write_scalar(&str_value(value)?)
+}
+
+/// Creates the specified scalar string dataset in the specified group.
+fn set_str(group: &Group, name: &str, value: &str) -> hdf5::Result<()> {
+    group.new_dataset::<VarLenUnicode>().shape(()).create(name)?.write_scalar(&str_value(value)?)
+}
@@ -0,0 +1,246 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::neural::Electrode;
+
+/// Contains a list of stimulus shapes of a protocol step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StimulusShape {
+    /// Constant amplitude for the specified duration (ms).
+    Step(f64, f64),
+    /// Linear ramp from the first amplitude to the second amplitude over the specified duration (ms).
+    Ramp(f64, f64, f64),
+    /// Train of pulses with the specified amplitude, pulse width (ms), time between pulse starts (ms) and pulse count.
+    PulseTrain(f64, f64, f64, u32),
+    /// Constant amplitude until the end of the sweep.
+    Hold(f64)
+}
+
+/// Contains data for a step of a stimulus protocol.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProtocolStep {
+    /// The start time of the step within a sweep (ms).
+    start: f64,
+    /// The stimulus shape.
+    shape: StimulusShape,
+    /// The amplitude increment per sweep. For a ramp, this is added to the end amplitude.
+    amplitude_inc: f64,
+    /// The start time increment per sweep (ms).
+    start_inc: f64
+}
+
+// ProtocolStep functions
+impl ProtocolStep {
+    /// Creates a new protocol step with the specified start time within a sweep (ms) and stimulus shape.
+    pub fn new(start: f64, shape: StimulusShape) -> Self {
+        Self {
+            start,
+            shape,
+            amplitude_inc: 0.0,
+            start_inc: 0.0
+        }
+    }
+
+    /// Sets the amplitude increment and start time increment (ms) per sweep.
+    pub fn set_increments(&mut self, amplitude_inc: f64, start_inc: f64) {
+        self.amplitude_inc = amplitude_inc;
+        self.start_inc = start_inc;
+    }
+
+    /// Returns the amplitude of the step at the specified sweep and time within the sweep (ms), or none if the step is
+    /// not active.
+    pub fn amplitude(&self, sweep: u32, t: f64) -> Option<f64> {
+        let inc = self.amplitude_inc * sweep as f64;
+        let t = t - (self.start + self.start_inc * sweep as f64);
+
+        if t < 0.0 {
+            return None;
+        }
+
+        match self.shape {
+            StimulusShape::Step(amp, duration) => {
+                if t < duration { Some(amp + inc) } else { None }
+            }
+            StimulusShape::Ramp(from, to, duration) => {
+                if t < duration { Some(from + (to + inc - from) * t / duration) } else { None }
+            }
+            StimulusShape::PulseTrain(amp, width, interval, count) => {
+                let n = (t / interval).floor();
+
+                if n < count as f64 && t - n * interval < width { Some(amp + inc) } else { None }
+            }
+            StimulusShape::Hold(amp) => Some(amp + inc)
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a stimulus protocol (a timeline of steps repeated over a number of sweeps).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Protocol {
+    /// The number of sweeps.
+    sweeps: u32,
+    /// The length of each sweep (ms).
+    sweep_length: f64,
+    /// The protocol steps.
+    steps: Vec<ProtocolStep>
+}
+
+// Protocol functions
+impl Protocol {
+    /// Creates a new empty protocol with the specified number of sweeps and sweep length (ms).
+    pub fn new(sweeps: u32, sweep_length: f64) -> Self {
+        Self {
+            sweeps,
+            sweep_length,
+            steps: vec![]
+        }
+    }
+
+    /// Creates a new F-I protocol of current steps: one step per sweep, starting at the specified amplitude and
+    /// increasing by the specified increment each sweep.
+    pub fn fi_steps(sweeps: u32, sweep_length: f64, start: f64, duration: f64, amplitude: f64, increment: f64) -> Self {
+        let mut step = ProtocolStep::new(start, StimulusShape::Step(amplitude, duration));
+        step.set_increments(increment, 0.0);
+
+        let mut protocol = Self::new(sweeps, sweep_length);
+        protocol.add_step(step);
+        protocol
+    }
+
+    /// Creates a new rheobase protocol with a single ramp from 0 to the specified amplitude.
+    pub fn rheobase_ramp(sweep_length: f64, start: f64, duration: f64, amplitude: f64) -> Self {
+        let mut protocol = Self::new(1, sweep_length);
+        protocol.add_step(ProtocolStep::new(start, StimulusShape::Ramp(0.0, amplitude, duration)));
+        protocol
+    }
+
+    /// Creates a new paired pulse protocol: two pulses whose interval increases by the specified increment (ms) each
+    /// sweep.
+    pub fn paired_pulses(sweeps: u32, sweep_length: f64, start: f64, amplitude: f64, width: f64, interval: f64,
+                         increment: f64) -> Self {
+        let mut second = ProtocolStep::new(start + interval, StimulusShape::Step(amplitude, width));
+        second.set_increments(0.0, increment);
+
+        let mut protocol = Self::new(sweeps, sweep_length);
+        protocol.add_step(ProtocolStep::new(start, StimulusShape::Step(amplitude, width)));
+        protocol.add_step(second);
+        protocol
+    }
+
+    /// Adds the specified step.
+    pub fn add_step(&mut self, step: ProtocolStep) {
+        self.steps.push(step);
+    }
+
+    /// Returns the total length of the protocol (ms).
+    pub fn duration(&self) -> f64 {
+        self.sweeps as f64 * self.sweep_length
+    }
+
+    /// Returns the command amplitude at the specified sweep and time within the sweep (ms), or none if no step is
+    /// active. The amplitudes of overlapping steps are added.
+    pub fn command(&self, sweep: u32, t: f64) -> Option<f64> {
+        self.steps.iter()
+            .filter_map(|step| step.amplitude(sweep, t))
+            .fold(None, |sum, amp| Some(sum.unwrap_or(0.0) + amp))
+    }
+
+    /// Compiles the protocol for the specified time step (ms) into a list of (time step index, command) changes. A
+    /// command of none turns the electrode off.
+    pub fn compile(&self, time_inc: f64) -> Vec<(u64, Option<f64>)> {
+        let steps_per_sweep = (self.sweep_length / time_inc).round() as u64;
+        let mut events: Vec<(u64, Option<f64>)> = vec![];
+        let mut prev: Option<f64> = None;
+
+        for sweep in 0..self.sweeps {
+            for n in 0..steps_per_sweep {
+                let cmd = self.command(sweep, n as f64 * time_inc);
+
+                if cmd != prev {
+                    events.push((sweep as u64 * steps_per_sweep + n, cmd));
+                    prev = cmd;
+                }
+            }
+        }
+
+        if prev.is_some() {
+            events.push((self.sweeps as u64 * steps_per_sweep, None));
+        }
+
+        events
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for playing back a compiled protocol on an electrode.
+pub struct ProtocolRunner {
+    /// The compiled protocol commands.
+    events: Vec<(u64, Option<f64>)>,
+    /// The index of the next command.
+    next: usize,
+    /// The number of time steps processed.
+    n: u64,
+    /// The electrode driven by the protocol.
+    target: Rc<RefCell<dyn Electrode>>
+}
+
+// ProtocolRunner functions
+impl ProtocolRunner {
+    /// Creates a new protocol runner that drives the specified electrode with the protocol compiled for the specified
+    /// time step (ms). The electrode should hold its voltage while on, such as a command electrode; an electrode that
+    /// turns itself off (such as a pulse electrode) is turned on again at the next time step of a command.
+    pub fn new(protocol: &Protocol, target: Rc<RefCell<dyn Electrode>>, time_inc: f64) -> Self {
+        Self {
+            events: protocol.compile(time_inc),
+            next: 0,
+            n: 0,
+            target
+        }
+    }
+
+    /// Returns the position of the protocol (the index of the next command and the number of time steps processed).
+    pub fn state(&self) -> (usize, u64) {
+        (self.next, self.n)
+    }
+
+    /// Restores the position of the protocol (the index of the next command and the number of time steps processed).
+    pub fn restore(&mut self, next: usize, n: u64) {
+        self.next = next.min(self.events.len());
+        self.n = n;
+    }
+
+    /// Returns true if every command of the protocol has been applied.
+    pub fn finished(&self) -> bool {
+        self.next >= self.events.len()
+    }
+
+    /// Applies the commands of the current time step to the electrode. This must be called once per time step,
+    /// before the electrode is processed. The current command is re-asserted every time step.
+    pub fn process(&mut self) {
+        let first = self.next;
+
+        while let Some(&(n, _)) = self.events.get(self.next) {
+            if n > self.n {
+                break;
+            }
+
+            self.next += 1;
+        }
+
+        let mut target = self.target.borrow_mut();
+
+        match self.next.checked_sub(1).and_then(|i| self.events[i].1) {
+            Some(v) => {
+                target.set_voltage(v);
+                target.on();
+            }
+            // Only turn the electrode off at a command, so it can be used by itself between protocols
+            None if self.next > first => target.off(),
+            None => {}
+        }
+
+        self.n += 1;
+    }
+}
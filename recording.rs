@@ -0,0 +1,241 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use crate::neural::{Electrode, Neurite, NeuriteID, NeuriteVar, Synapse, SynapseVar, SynapticID};
+
+/// Contains a list of electrode variables that can be recorded by a probe.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ElectrodeVar {
+    Output,
+    Voltage
+}
+
+/// Contains a list of variables that can be recorded by a probe.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProbeVar {
+    Neurite(NeuriteVar),
+    Synapse(SynapseVar),
+    Electrode(ElectrodeVar)
+}
+
+// ProbeVar functions
+impl ProbeVar {
+    /// Returns the name of the variable.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProbeVar::Neurite(NeuriteVar::U) => "u",
+            ProbeVar::Neurite(NeuriteVar::V) => "v",
+            ProbeVar::Neurite(NeuriteVar::Y) => "y",
+            ProbeVar::Neurite(NeuriteVar::VCC) => "vcc",
+            ProbeVar::Neurite(NeuriteVar::VPC) => "vpc",
+            ProbeVar::Synapse(SynapseVar::X) => "x",
+            ProbeVar::Synapse(SynapseVar::AX) => "ax",
+            ProbeVar::Synapse(SynapseVar::BX) => "bx",
+            ProbeVar::Synapse(SynapseVar::W) => "w",
+            ProbeVar::Synapse(SynapseVar::STPF) => "stp_f",
+            ProbeVar::Synapse(SynapseVar::STPD) => "stp_d",
+            ProbeVar::Synapse(SynapseVar::STPY) => "stp_y",
+            ProbeVar::Electrode(ElectrodeVar::Output) => "output",
+            ProbeVar::Electrode(ElectrodeVar::Voltage) => "voltage"
+        }
+    }
+}
+
+/// Contains a list of probe targets, by ID (or by index for an electrode of a simulation).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProbeTarget {
+    Neurite(NeuriteID),
+    Synapse(SynapticID),
+    Electrode(usize)
+}
+
+/// Contains a list of probe sources (the recorded neurite, synapse or electrode).
+#[derive(Clone)]
+pub enum ProbeSource {
+    Neurite(Rc<RefCell<Neurite>>),
+    Synapse(Rc<RefCell<dyn Synapse>>),
+    Electrode(Rc<RefCell<dyn Electrode>>)
+}
+
+// ProbeSource functions
+impl ProbeSource {
+    /// Returns the current value of the specified variable, or none if the source does not have the variable.
+    pub fn read(&self, var: ProbeVar) -> Option<f64> {
+        match (self, var) {
+            (ProbeSource::Neurite(n), ProbeVar::Neurite(var)) => Some(n.borrow().probe(var)),
+            (ProbeSource::Synapse(s), ProbeVar::Synapse(var)) => s.borrow().probe(var),
+            (ProbeSource::Electrode(e), ProbeVar::Electrode(ElectrodeVar::Output)) => Some(e.borrow().output()),
+            (ProbeSource::Electrode(e), ProbeVar::Electrode(ElectrodeVar::Voltage)) => Some(e.borrow().voltage()),
+            _ => None
+        }
+    }
+}
+
+/// Contains a list of buffer modes for recorded samples.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BufferMode {
+    /// Keeps every sample.
+    Unbounded,
+    /// Keeps the specified number of first samples and stops recording.
+    Bounded(usize),
+    /// Keeps the specified number of last samples, discarding the oldest.
+    Ring(usize)
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a probe that records time-stamped samples of variables of a neurite, synapse or electrode.
+pub struct Probe {
+    /// The recorded source.
+    source: ProbeSource,
+    /// The recorded variables.
+    vars: Vec<ProbeVar>,
+    /// The sampling interval (ms).
+    interval: f64,
+    /// The time remaining until the next sample (ms).
+    a: f64,
+    /// The buffer mode.
+    mode: BufferMode,
+    /// The sample times (ms).
+    times: VecDeque<f64>,
+    /// The samples, one row of variable values per sample time.
+    samples: VecDeque<Vec<f64>>
+}
+
+// Probe functions
+impl Probe {
+    /// Creates a new probe that records the specified variables of the source at the specified sampling interval (ms).
+    /// Every variable must belong to the source.
+    pub fn new(source: ProbeSource, vars: Vec<ProbeVar>, interval: f64, mode: BufferMode) -> Result<Self, &'static str> {
+        if vars.iter().any(|&var| source.read(var).is_none()) {
+            return Err("Variable does not belong to the probe source.");
+        }
+
+        Ok(Self {
+            source,
+            vars,
+            interval,
+            a: 0.0,
+            mode,
+            times: VecDeque::new(),
+            samples: VecDeque::new()
+        })
+    }
+
+    /// Processes the probe at the end of a time step of the specified length (ms), recording a sample at the specified
+    /// simulation time (ms) when the sampling interval has elapsed.
+    pub fn process(&mut self, t: f64, time: f64) {
+        self.a -= time;
+
+        if self.a > 0.5 * time {
+            return;
+        }
+
+        self.a = (self.a + self.interval).max(0.0);
+
+        match self.mode {
+            BufferMode::Bounded(n) if self.times.len() >= n => return,
+            BufferMode::Ring(n) if self.times.len() >= n => {
+                self.times.pop_front();
+                self.samples.pop_front();
+            }
+            _ => {}
+        }
+
+        let sample = self.vars.iter().map(|&var| self.source.read(var).unwrap_or(0.0)).collect();
+
+        self.times.push_back(t);
+        self.samples.push_back(sample);
+    }
+
+    /// Returns the recorded source.
+    pub fn source(&self) -> &ProbeSource {
+        &self.source
+    }
+
+    /// Returns the recorded variables.
+    pub fn vars(&self) -> &[ProbeVar] {
+        &self.vars
+    }
+
+    /// Returns the sample times (ms).
+    pub fn times(&self) -> &VecDeque<f64> {
+        &self.times
+    }
+
+    /// Returns the samples, one row of variable values per sample time.
+    pub fn samples(&self) -> &VecDeque<Vec<f64>> {
+        &self.samples
+    }
+
+    /// Returns the recorded trace of the specified variable, or none if the variable is not recorded.
+    pub fn trace(&self, var: ProbeVar) -> Option<Vec<f64>> {
+        let i = self.vars.iter().position(|&v| v == var)?;
+
+        Some(self.samples.iter().map(|s| s[i]).collect())
+    }
+
+    /// Returns true if the probe is bounded and has stopped recording.
+    pub fn full(&self) -> bool {
+        matches!(self.mode, BufferMode::Bounded(n) if self.times.len() >= n)
+    }
+
+    /// Removes every recorded sample.
+    pub fn clear(&mut self) {
+        self.times.clear();
+        self.samples.clear();
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for a recorder of spike events of a set of neurites.
+pub struct SpikeRecorder {
+    /// The recorded neurites.
+    neurites: Vec<Rc<RefCell<Neurite>>>,
+    /// The recorded spike events (neurite ID and spike time (ms)), in order of time step.
+    events: Vec<(NeuriteID, f64)>
+}
+
+// SpikeRecorder functions
+impl SpikeRecorder {
+    /// Creates a new spike recorder for the specified neurites.
+    pub fn new(neurites: Vec<Rc<RefCell<Neurite>>>) -> Self {
+        Self {
+            neurites,
+            events: vec![]
+        }
+    }
+
+    /// Processes the recorder at the end of a time step that started at the specified simulation time (ms). Spike times
+    /// include the neurite's spike time offset within the time step.
+    pub fn process(&mut self, t: f64) {
+        for n in &self.neurites {
+            let n = n.borrow();
+
+            if n.y() > 0.0 {
+                self.events.push((n.id(), t + n.spike_offset()));
+            }
+        }
+    }
+
+    /// Returns the recorded neurites.
+    pub fn neurites(&self) -> &[Rc<RefCell<Neurite>>] {
+        &self.neurites
+    }
+
+    /// Returns the recorded spike events (neurite ID and spike time (ms)).
+    pub fn events(&self) -> &[(NeuriteID, f64)] {
+        &self.events
+    }
+
+    /// Returns the recorded spike times (ms) of the specified neurite.
+    pub fn spike_times(&self, id: NeuriteID) -> Vec<f64> {
+        self.events.iter().filter(|e| e.0 == id).map(|e| e.1).collect()
+    }
+
+    /// Removes every recorded spike event.
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
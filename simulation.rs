@@ -0,0 +1,411 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::checkpoint::{Checkpoint, CHECKPOINT_VERSION};
+use crate::model::{Model, ModelFile, ModelFormat};
+use crate::neural::{DopamineSignal, Electrode, Neurite, NeuriteID, Neuron, Sensor, Synapse, SynapticID};
+use crate::protocol::{Protocol, ProtocolRunner};
+use crate::recording::{BufferMode, Probe, ProbeSource, ProbeTarget, ProbeVar, SpikeRecorder};
+use crate::system::{rng_stream, TIME_INC};
+
+/// Contains data for a network simulation of electrodes, sensors, synapses and neurons.
+pub struct Simulation {
+    /// The simulation time step (ms).
+    time_inc: f64,
+    /// The random number generator seed.
+    seed: u64,
+    /// The current simulation time (ms).
+    time: f64,
+    /// The number of time steps processed.
+    steps: u64,
+    /// The simulation's protocols.
+    protocols: Vec<ProtocolRunner>,
+    /// The simulation's electrodes.
+    electrodes: Vec<Rc<RefCell<dyn Electrode>>>,
+    /// The simulation's sensors.
+    sensors: Vec<Rc<RefCell<dyn Sensor>>>,
+    /// The simulation's synapses.
+    synapses: Vec<Rc<RefCell<dyn Synapse>>>,
+    /// The simulation's neurons.
+    neurons: Vec<Neuron>,
+    /// The simulation's standalone neurites (neurites that are not part of a neuron).
+    neurites: Vec<Rc<RefCell<Neurite>>>,
+    /// The global dopamine (reward) signal.
+    dopamine: Rc<RefCell<DopamineSignal>>,
+    /// The simulation's additional (local) dopamine signals.
+    dopamine_signals: Vec<Rc<RefCell<DopamineSignal>>>,
+    /// The simulation's probes.
+    probes: Vec<Probe>,
+    /// The simulation's spike recorders.
+    spike_recorders: Vec<SpikeRecorder>
+}
+
+// Default for Simulation
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new(TIME_INC, 0)
+    }
+}
+
+// Simulation functions
+impl Simulation {
+    /// Creates a new empty simulation with the specified time step (ms) and random number generator seed.
+    pub fn new(time_inc: f64, seed: u64) -> Self {
+        Self {
+            time_inc,
+            seed,
+            time: 0.0,
+            steps: 0,
+            protocols: vec![],
+            electrodes: vec![],
+            sensors: vec![],
+            synapses: vec![],
+            neurons: vec![],
+            neurites: vec![],
+            dopamine: Rc::new(RefCell::new(DopamineSignal::new(200.0, 0.01))),
+            dopamine_signals: vec![],
+            probes: vec![],
+            spike_recorders: vec![]
+        }
+    }
+
+    /// Returns the simulation time step (ms).
+    pub fn time_inc(&self) -> f64 {
+        self.time_inc
+    }
+
+    /// Returns the random number generator seed.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the current simulation time (ms).
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Adds the specified electrode. The electrode is seeded with its own random number generator sub-stream, chosen by
+    /// the order the electrodes are added in. Adding an electrode does not change the output of previously added
+    /// electrodes, but inserting or removing one changes the sub-streams of the electrodes added after it.
+    pub fn add_electrode(&mut self, electrode: Rc<RefCell<dyn Electrode>>) {
+        electrode.borrow_mut().seed(rng_stream(self.seed, self.electrodes.len() as u64));
+        self.electrodes.push(electrode);
+    }
+
+    /// Adds the specified protocol, driving the specified electrode from the current simulation time. The electrode
+    /// must also be added to the simulation.
+    pub fn add_protocol(&mut self, protocol: &Protocol, target: Rc<RefCell<dyn Electrode>>) {
+        self.protocols.push(ProtocolRunner::new(protocol, target, self.time_inc));
+    }
+
+    /// Adds the specified sensor. Sensors are processed in the order they were added, so a sensor that reads another
+    /// sensor (such as a spike encoder) must be added after it. Sensors are seeded from sub-streams separate from the
+    /// electrodes', chosen by the order the sensors are added in, so inserting or removing a sensor changes the
+    /// sub-streams of the sensors added after it.
+    pub fn add_sensor(&mut self, sensor: Rc<RefCell<dyn Sensor>>) {
+        sensor.borrow_mut().seed(rng_stream(self.seed, (1 << 32) + self.sensors.len() as u64));
+        self.sensors.push(sensor);
+    }
+
+    /// Adds the specified synapse.
+    pub fn add_synapse(&mut self, synapse: Rc<RefCell<dyn Synapse>>) {
+        self.synapses.push(synapse);
+    }
+
+    /// Adds the specified neuron. Every neurite of the neuron is processed by the simulation.
+    pub fn add_neuron(&mut self, neuron: Neuron) {
+        self.neurons.push(neuron);
+    }
+
+    /// Adds the specified standalone neurite. Neurites that are part of an added neuron must not be added again.
+    pub fn add_neurite(&mut self, neurite: Rc<RefCell<Neurite>>) {
+        self.neurites.push(neurite);
+    }
+
+    /// Adds the specified local dopamine signal (a signal other than the global dopamine signal).
+    pub fn add_dopamine_signal(&mut self, dopamine: Rc<RefCell<DopamineSignal>>) {
+        self.dopamine_signals.push(dopamine);
+    }
+
+    /// Returns the global dopamine signal.
+    pub fn dopamine(&self) -> Rc<RefCell<DopamineSignal>> {
+        Rc::clone(&self.dopamine)
+    }
+
+    /// Delivers the specified amount of reward to the global dopamine signal.
+    pub fn reward(&mut self, amount: f64) {
+        self.dopamine.borrow_mut().reward(amount);
+    }
+
+    /// Returns the simulation's neurons.
+    pub fn neurons(&self) -> &[Neuron] {
+        &self.neurons
+    }
+
+    /// Returns the simulation's electrodes.
+    pub fn electrodes(&self) -> &[Rc<RefCell<dyn Electrode>>] {
+        &self.electrodes
+    }
+
+    /// Saves the simulation's neurons, standalone neurites, synapses, electrodes and dopamine signals to a model file at
+    /// the specified path in the specified format. Protocols, sensors and recordings are not saved.
+    pub fn save_model(&self, path: &str, format: ModelFormat) -> Result<(), String> {
+        let mut dopamine = vec![Rc::clone(&self.dopamine)];
+        dopamine.extend(self.dopamine_signals.iter().cloned());
+
+        let model = Model {
+            neurons: self.neurons.clone(),
+            neurites: self.neurites.clone(),
+            synapses: self.synapses.clone(),
+            electrodes: self.electrodes.clone(),
+            other_electrodes: vec![],
+            dopamine
+        };
+
+        ModelFile::new(&model)?.save(path, format)
+    }
+
+    /// Creates a new simulation with the specified time step (ms) and random number generator seed from the model file
+    /// at the specified path in the specified format. Every electrode of the model is reseeded from the seed, including
+    /// electrodes that are not part of the simulation (such as the conductance of a conductance electrode). Electrodes
+    /// are loaded turned off.
+    pub fn load_model(path: &str, format: ModelFormat, time_inc: f64, seed: u64) -> Result<Self, String> {
+        let model = ModelFile::load(path, format)?.build()?;
+        let mut sim = Self::new(time_inc, seed);
+        let mut dopamine = model.dopamine.into_iter();
+
+        if let Some(d) = dopamine.next() {
+            sim.dopamine = d;
+        }
+
+        sim.dopamine_signals = dopamine.collect();
+
+        for e in model.electrodes {
+            sim.add_electrode(e);
+        }
+
+        for (i, e) in model.other_electrodes.iter().enumerate() {
+            e.borrow_mut().seed(rng_stream(seed, (2 << 32) + i as u64));
+        }
+
+        sim.synapses = model.synapses;
+        sim.neurons = model.neurons;
+        sim.neurites = model.neurites;
+
+        Ok(sim)
+    }
+
+    /// Returns a checkpoint of the dynamic state of the simulation's neurites, synapses, electrodes and sensors
+    /// (including their random number generators and pending spikes), dopamine signals and protocols. Recordings are
+    /// not included. If an electrode cannot be checkpointed, this returns an error message.
+    pub fn checkpoint(&self) -> Result<Checkpoint, String> {
+        let electrodes = self.electrodes.iter()
+            .enumerate()
+            .map(|(i, e)| e.borrow().state().ok_or(format!("Electrode {} cannot be checkpointed.", i)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut dopamine = vec![self.dopamine.borrow().state()];
+        dopamine.extend(self.dopamine_signals.iter().map(|d| d.borrow().state()));
+
+        Ok(Checkpoint {
+            version: CHECKPOINT_VERSION,
+            time_inc: self.time_inc,
+            steps: self.steps,
+            neurites: self.all_neurites().iter().map(|n| n.borrow().state()).collect(),
+            synapses: self.all_synapses().iter().map(|s| s.borrow().state()).collect(),
+            electrodes,
+            sensors: self.sensors.iter().map(|s| s.borrow().state()).collect(),
+            dopamine,
+            protocols: self.protocols.iter().map(|p| p.state()).collect()
+        })
+    }
+
+    /// Restores the specified checkpoint, continuing the simulation from the checkpoint's time step. The simulation
+    /// must have the same model, sensors and protocols as the simulation the checkpoint was taken from: neurites and
+    /// synapses are matched by ID, and electrodes, sensors, dopamine signals and protocols by order. If the checkpoint
+    /// does not match the simulation, this returns an error message and the simulation may be partially restored.
+    pub fn restore(&mut self, checkpoint: &Checkpoint) -> Result<(), String> {
+        if checkpoint.time_inc != self.time_inc {
+            return Err(format!("Checkpoint has time step {} ms, but the simulation has time step {} ms.",
+                               checkpoint.time_inc, self.time_inc));
+        }
+
+        let neurites = self.all_neurites();
+        let synapses = self.all_synapses();
+
+        if checkpoint.neurites.len() != neurites.len() || checkpoint.synapses.len() != synapses.len()
+            || checkpoint.electrodes.len() != self.electrodes.len()
+            || checkpoint.sensors.len() != self.sensors.len()
+            || checkpoint.dopamine.len() != self.dopamine_signals.len() + 1
+            || checkpoint.protocols.len() != self.protocols.len() {
+            return Err("Checkpoint does not match the simulation.".to_string());
+        }
+
+        for state in &checkpoint.neurites {
+            let n = neurites.iter()
+                .find(|n| n.borrow().id() == state.id)
+                .ok_or(format!("Neurite {} of the checkpoint not found.", state.id.uuid()))?;
+            n.borrow_mut().restore(state);
+        }
+
+        for state in &checkpoint.synapses {
+            let s = synapses.iter()
+                .find(|s| s.borrow().syn_id() == state.id)
+                .ok_or(format!("Synapse {} of the checkpoint not found.", state.id.uuid()))?;
+            s.borrow_mut().restore(state);
+        }
+
+        for (e, state) in self.electrodes.iter().zip(&checkpoint.electrodes) {
+            e.borrow_mut().restore(state)?;
+        }
+
+        for (s, state) in self.sensors.iter().zip(&checkpoint.sensors) {
+            s.borrow_mut().restore(state);
+        }
+
+        self.dopamine.borrow_mut().restore(checkpoint.dopamine[0]);
+
+        for (d, &state) in self.dopamine_signals.iter().zip(&checkpoint.dopamine[1..]) {
+            d.borrow_mut().restore(state);
+        }
+
+        for (p, &(next, n)) in self.protocols.iter_mut().zip(&checkpoint.protocols) {
+            p.restore(next, n);
+        }
+
+        self.steps = checkpoint.steps;
+        self.time = self.steps as f64 * self.time_inc;
+        Ok(())
+    }
+
+    /// Adds a probe that records the specified variables of the specified target at the specified sampling interval
+    /// (ms), and returns the probe's index. The target must already be part of the simulation.
+    pub fn add_probe(&mut self, target: ProbeTarget, vars: Vec<ProbeVar>, interval: f64, mode: BufferMode)
+        -> Result<usize, &'static str> {
+        let source = match target {
+            ProbeTarget::Neurite(id) => ProbeSource::Neurite(self.find_neurite(id).ok_or("Neurite not found.")?),
+            ProbeTarget::Synapse(id) => ProbeSource::Synapse(self.find_synapse(id).ok_or("Synapse not found.")?),
+            ProbeTarget::Electrode(i) => {
+                ProbeSource::Electrode(Rc::clone(self.electrodes.get(i).ok_or("Electrode not found.")?))
+            }
+        };
+
+        self.probes.push(Probe::new(source, vars, interval, mode)?);
+        Ok(self.probes.len() - 1)
+    }
+
+    /// Returns the probe at the specified index.
+    pub fn probe(&self, index: usize) -> Option<&Probe> {
+        self.probes.get(index)
+    }
+
+    /// Adds a spike recorder for the specified neurites and returns the recorder's index. The neurites must already be
+    /// part of the simulation.
+    pub fn add_spike_recorder(&mut self, ids: &[NeuriteID]) -> Result<usize, &'static str> {
+        let neurites = ids.iter()
+            .map(|&id| self.find_neurite(id).ok_or("Neurite not found."))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.spike_recorders.push(SpikeRecorder::new(neurites));
+        Ok(self.spike_recorders.len() - 1)
+    }
+
+    /// Returns the spike recorder at the specified index.
+    pub fn spike_recorder(&self, index: usize) -> Option<&SpikeRecorder> {
+        self.spike_recorders.get(index)
+    }
+
+    /// Returns every neurite of the simulation's neurons and every standalone neurite.
+    fn all_neurites(&self) -> Vec<Rc<RefCell<Neurite>>> {
+        let mut neurites: Vec<Rc<RefCell<Neurite>>> = self.neurons.iter().flat_map(|n| n.neurites()).collect();
+        neurites.extend(self.neurites.iter().cloned());
+        neurites
+    }
+
+    /// Returns the neurite with the specified ID.
+    fn find_neurite(&self, id: NeuriteID) -> Option<Rc<RefCell<Neurite>>> {
+        self.all_neurites().into_iter().find(|n| n.borrow().id() == id)
+    }
+
+    /// Returns the synapse with the specified ID, from the simulation's synapses or the synapses attached to its
+    /// neurites.
+    fn find_synapse(&self, id: SynapticID) -> Option<Rc<RefCell<dyn Synapse>>> {
+        self.synapses.iter()
+            .cloned()
+            .chain(self.all_neurites().iter().flat_map(|n| n.borrow().synapses()))
+            .find(|s| s.borrow().syn_id() == id)
+    }
+
+    /// Returns the simulation's synapses followed by the other synapses attached to its neurites, without duplicates.
+    fn all_synapses(&self) -> Vec<Rc<RefCell<dyn Synapse>>> {
+        let mut synapses = self.synapses.clone();
+
+        for s in self.all_neurites().iter().flat_map(|n| n.borrow().synapses()) {
+            let id = s.borrow().syn_id();
+
+            if !synapses.iter().any(|t| t.borrow().syn_id() == id) {
+                synapses.push(s);
+            }
+        }
+
+        synapses
+    }
+
+    /// Processes one time step. Inputs (protocols, electrodes, sensors and dopamine signals) are processed first, then
+    /// synapses, then the neurite compartments of every neuron and standalone neurite, each in the order they were
+    /// added. Spike recorders and probes record last, probes at the time at the end of the step.
+    pub fn step(&mut self) {
+        // Inputs
+        for p in &mut self.protocols {
+            p.process();
+        }
+
+        for e in &self.electrodes {
+            e.borrow_mut().process(self.time_inc);
+        }
+
+        for s in &self.sensors {
+            s.borrow_mut().process(self.time_inc);
+        }
+
+        self.dopamine.borrow_mut().process(self.time_inc);
+
+        for d in &self.dopamine_signals {
+            d.borrow_mut().process(self.time_inc);
+        }
+
+        // Synapses
+        for s in &self.synapses {
+            s.borrow_mut().process(self.time_inc);
+        }
+
+        // Compartments
+        for n in &mut self.neurons {
+            n.process(self.time_inc);
+        }
+
+        for n in &self.neurites {
+            n.borrow_mut().process(self.time_inc);
+        }
+
+        // Recording
+        for r in &mut self.spike_recorders {
+            r.process(self.time);
+        }
+
+        self.steps += 1;
+        self.time = self.steps as f64 * self.time_inc;
+
+        for p in &mut self.probes {
+            p.process(self.time, self.time_inc);
+        }
+    }
+
+    /// Processes time steps for the specified length of time (ms).
+    pub fn run_for(&mut self, ms: f64) {
+        let steps = (ms / self.time_inc).round() as u64;
+
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+}
@@ -0,0 +1,431 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::neural::{Neurite, NeuriteID, NeuriteType, Neuron, SpikeModel};
+
+/// Contains data for a point of an SWC reconstruction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SwcPoint {
+    /// The point ID.
+    pub id: i64,
+    /// The structure type code (1 soma, 2 axon, 3 basal dendrite, 4 apical dendrite).
+    pub kind: u32,
+    /// The x coordinate (µm).
+    pub x: f64,
+    /// The y coordinate (µm).
+    pub y: f64,
+    /// The z coordinate (µm).
+    pub z: f64,
+    /// The radius (µm).
+    pub radius: f64,
+    /// The parent point ID (-1 for a root point).
+    pub parent: i64
+}
+
+// SwcPoint functions
+impl SwcPoint {
+    /// Returns the distance (µm) to the specified point.
+    pub fn distance(&self, other: &SwcPoint) -> f64 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2) + (self.z - other.z).powi(2)).sqrt()
+    }
+}
+
+/// Contains a list of rules for splitting dendrites into proximal and distal (or trunk and tuft) neurites.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SwcSplit {
+    /// Points with at least the specified branch order are distal. Points on a stem leaving the soma have order 0.
+    BranchOrder(u32),
+    /// Points at least the specified path distance (µm) from the soma are distal.
+    Distance(f64)
+}
+
+// SwcSplit functions
+impl SwcSplit {
+    /// Returns true if a point with the specified branch order and path distance (µm) from the soma is distal.
+    fn distal(&self, order: u32, distance: f64) -> bool {
+        match *self {
+            SwcSplit::BranchOrder(n) => order >= n,
+            SwcSplit::Distance(d) => distance >= d
+        }
+    }
+}
+
+/// Contains data for a compartment that could not be placed in the neurite tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwcUnplaced {
+    /// The IDs of the points lumped into the compartment.
+    pub points: Vec<i64>,
+    /// The neurite type of the compartment, or none if the structure type code has no neurite type.
+    pub neurite_type: Option<NeuriteType>,
+    /// The reason the compartment could not be placed.
+    pub reason: &'static str
+}
+
+/// Contains data for a neurite placed in the neurite tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwcPlaced {
+    /// The neurite ID of the neurite.
+    pub neurite: NeuriteID,
+    /// The neurite type.
+    pub neurite_type: NeuriteType,
+    /// The IDs of the points lumped into the neurite.
+    pub points: Vec<i64>,
+    /// The lumped path length (µm) of the neurite (0 for the soma).
+    pub length: f64,
+    /// The mean radius (µm) of the points lumped into the neurite.
+    pub radius: f64
+}
+
+/// Contains data for the report of an SWC import.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwcReport {
+    /// The number of points read.
+    pub points: usize,
+    /// The number of neurites placed in the neurite tree, including the soma.
+    pub placed: usize,
+    /// The neurites placed in the neurite tree, starting with the soma.
+    pub neurites: Vec<SwcPlaced>,
+    /// The compartments that could not be placed. Points of a compartment that could not be placed are not part of the
+    /// neuron, and neither are the compartments beyond it.
+    pub unplaced: Vec<SwcUnplaced>
+}
+
+/// Contains data for a lumped compartment of an SWC reconstruction.
+#[derive(Clone, Debug, PartialEq)]
+struct SwcCompartment {
+    /// The neurite type, or none if the structure type code has no neurite type.
+    neurite_type: Option<NeuriteType>,
+    /// The index of the parent compartment, or none if the parent is the soma.
+    parent: Option<usize>,
+    /// The IDs of the points lumped into the compartment.
+    points: Vec<i64>,
+    /// The path length (µm) of the compartment.
+    length: f64,
+    /// The sum of the radii (µm) of the points lumped into the compartment.
+    radius: f64,
+    /// The reason the compartment cannot be placed, if it is known before placing it.
+    reason: Option<&'static str>
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Contains data for importing SWC reconstructions (the NeuroMorpho.org format) as neurons. Points are lumped into one
+/// neurite per unbranched section of the same type, split into neurites no longer than the compartment length, and
+/// every soma point is lumped into the soma neurite.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwcImport {
+    /// The maximal path length (µm) of a lumped neurite (infinite lumps every unbranched section into one neurite).
+    length: f64,
+    /// The rule for splitting basal dendrites into basal proximal and basal distal neurites.
+    basal: SwcSplit,
+    /// The rule for splitting apical dendrites into apical trunk and apical tuft neurites.
+    apical: SwcSplit,
+    /// The spike model of the soma neurite.
+    soma_model: SpikeModel,
+    /// The spike model of the dendrite neurites.
+    dendrite_model: SpikeModel,
+    /// The spike model of the axon neurites.
+    axon_model: SpikeModel
+}
+
+// Default for SwcImport
+impl Default for SwcImport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SwcImport functions
+impl SwcImport {
+    /// Creates a new SWC import that lumps every unbranched section into one neurite, with basal dendrites distal from
+    /// the first branch point on and apical dendrites in the tuft from the first branch point on.
+    pub fn new() -> Self {
+        Self {
+            length: f64::INFINITY,
+            basal: SwcSplit::BranchOrder(1),
+            apical: SwcSplit::BranchOrder(1),
+            soma_model: SpikeModel::RegularSpikingPyramidalI,
+            dendrite_model: SpikeModel::RegularSpikingPyramidalL5L6Dendrite,
+            axon_model: SpikeModel::RegularSpikingPyramidalI
+        }
+    }
+
+    /// Sets the maximal path length (µm) of a lumped neurite (infinite lumps every unbranched section into one
+    /// neurite).
+    pub fn set_length(&mut self, length: f64) {
+        self.length = length;
+    }
+
+    /// Sets the rule for splitting basal dendrites into basal proximal and basal distal neurites.
+    pub fn set_basal_split(&mut self, split: SwcSplit) {
+        self.basal = split;
+    }
+
+    /// Sets the rule for splitting apical dendrites into apical trunk and apical tuft neurites.
+    pub fn set_apical_split(&mut self, split: SwcSplit) {
+        self.apical = split;
+    }
+
+    /// Sets the spike models of the soma, dendrite and axon neurites.
+    pub fn set_spike_models(&mut self, soma: SpikeModel, dendrite: SpikeModel, axon: SpikeModel) {
+        self.soma_model = soma;
+        self.dendrite_model = dendrite;
+        self.axon_model = axon;
+    }
+
+    /// Reads the SWC file at the specified path and returns the neuron and the import report.
+    pub fn read(&self, path: &str) -> Result<(Neuron, SwcReport), String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read SWC file {}: {}", path, e))?;
+
+        self.import(&parse(&text)?)
+    }
+
+    /// Builds a neuron from the specified SWC points and returns it and the import report. Compartments are placed
+    /// following the neurite child rules; a compartment that cannot be placed is reported along with the reason. If
+    /// there are no soma points, this returns an error message.
+    pub fn import(&self, points: &[SwcPoint]) -> Result<(Neuron, SwcReport), String> {
+        let index: HashMap<i64, usize> = points.iter().enumerate().map(|(i, p)| (p.id, i)).collect();
+
+        if index.len() != points.len() {
+            return Err("SWC points have duplicate IDs.".to_string());
+        }
+
+        if !points.iter().any(|p| p.kind == 1) {
+            return Err("SWC points have no soma point.".to_string());
+        }
+
+        let mut children: Vec<Vec<usize>> = vec![vec![]; points.len()];
+
+        for (i, p) in points.iter().enumerate() {
+            if let Some(&parent) = index.get(&p.parent) {
+                children[parent].push(i);
+            }
+        }
+
+        // Each entry holds a point, the compartment of its parent point (none for the soma), whether the point may
+        // continue that compartment, and the branch order and path distance (µm) from the soma of its parent point
+        let mut stack: Vec<(usize, Option<usize>, bool, u32, f64)> = vec![];
+        let mut compartments: Vec<SwcCompartment> = vec![];
+
+        let children_of = &children;
+        let stems = move |i: usize, parent: Option<usize>| {
+            children_of[i].iter().rev().filter(move |&&n| points[n].kind != 1).map(move |&n| (n, parent, false, 0, 0.0))
+        };
+
+        // Points that are not connected to the soma cannot be placed, and neither can the points beyond them
+        for (i, p) in points.iter().enumerate().rev() {
+            if p.kind != 1 && !index.contains_key(&p.parent) {
+                let reason = match p.parent {
+                    -1 => "Point is not connected to the soma.",
+                    _ => "Parent point not found."
+                };
+
+                compartments.push(SwcCompartment {
+                    neurite_type: self.neurite_type(p.kind, 0, 0.0),
+                    parent: None,
+                    points: vec![p.id],
+                    length: 0.0,
+                    radius: p.radius,
+                    reason: Some(reason)
+                });
+
+                stack.extend(stems(i, Some(compartments.len() - 1)));
+            }
+        }
+
+        // Stems leave the soma at soma points and are processed first, in the order of the points
+        for (i, p) in points.iter().enumerate().rev() {
+            if p.kind == 1 {
+                stack.extend(stems(i, None));
+            }
+        }
+
+        let mut visited = vec![false; points.len()];
+
+        while let Some((i, parent, cont, order, distance)) = stack.pop() {
+            let p = &points[i];
+            visited[i] = true;
+            let step = index.get(&p.parent).map(|&q| p.distance(&points[q])).unwrap_or(0.0);
+            let distance = distance + step;
+            // Distal neurites cannot be children of the soma, so stems leave the soma as proximal (or trunk) neurites
+            let neurite_type = match parent {
+                Some(_) => self.neurite_type(p.kind, order, distance),
+                None => self.neurite_type(p.kind, 0, 0.0).map(proximal)
+            };
+
+            // Continue the parent compartment unless the type changes or the compartment would grow too long
+            let current = match parent {
+                Some(c) if cont && compartments[c].neurite_type == neurite_type
+                    && compartments[c].length + step <= self.length => {
+                    compartments[c].length += step;
+                    c
+                }
+                _ => {
+                    compartments.push(SwcCompartment {
+                        neurite_type,
+                        parent,
+                        points: vec![],
+                        length: step,
+                        radius: 0.0,
+                        reason: if neurite_type.is_none() { Some("Structure type has no neurite type.") } else { None }
+                    });
+
+                    compartments.len() - 1
+                }
+            };
+
+            compartments[current].points.push(p.id);
+            compartments[current].radius += p.radius;
+
+            // Every branch of a branch point starts a new compartment with a higher branch order
+            let next: Vec<usize> = children[i].iter().copied().filter(|&c| points[c].kind != 1).collect();
+
+            if next.len() > 1 {
+                stack.extend(next.iter().rev().map(|&c| (c, Some(current), false, order + 1, distance)));
+            }
+            else {
+                stack.extend(next.iter().map(|&c| (c, Some(current), true, order, distance)));
+            }
+        }
+
+        // Points in a cycle of parents are never reached from a root
+        for (i, p) in points.iter().enumerate() {
+            if p.kind != 1 && !visited[i] && index.contains_key(&p.parent) {
+                compartments.push(SwcCompartment {
+                    neurite_type: self.neurite_type(p.kind, 0, 0.0),
+                    parent: None,
+                    points: vec![p.id],
+                    length: 0.0,
+                    radius: p.radius,
+                    reason: Some("Point is not connected to the soma.")
+                });
+            }
+        }
+
+        Ok(self.place(points, &compartments))
+    }
+
+    /// Returns the neurite type of a point with the specified structure type code, branch order and path distance (µm)
+    /// from the soma, or none if the structure type code has no neurite type.
+    fn neurite_type(&self, kind: u32, order: u32, distance: f64) -> Option<NeuriteType> {
+        match kind {
+            1 => Some(NeuriteType::Soma),
+            2 => Some(NeuriteType::Axon),
+            3 if self.basal.distal(order, distance) => Some(NeuriteType::BasalDistal),
+            3 => Some(NeuriteType::BasalProximal),
+            4 if self.apical.distal(order, distance) => Some(NeuriteType::ApicalTuft),
+            4 => Some(NeuriteType::ApicalTrunk),
+            _ => None
+        }
+    }
+
+    /// Places the specified compartments of the specified points in a new neuron's neurite tree and returns the neuron
+    /// and the import report. Every compartment must follow its parent compartment.
+    fn place(&self, points: &[SwcPoint], compartments: &[SwcCompartment]) -> (Neuron, SwcReport) {
+        let soma = Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, self.soma_model)));
+        let soma_points: Vec<&SwcPoint> = points.iter().filter(|p| p.kind == 1).collect();
+        let mut neurites: Vec<Option<Rc<RefCell<Neurite>>>> = vec![];
+        let mut report = SwcReport {
+            points: points.len(),
+            placed: 1,
+            neurites: vec![SwcPlaced {
+                neurite: soma.borrow().id(),
+                neurite_type: NeuriteType::Soma,
+                points: soma_points.iter().map(|p| p.id).collect(),
+                length: 0.0,
+                radius: soma_points.iter().map(|p| p.radius).sum::<f64>() / soma_points.len() as f64
+            }],
+            unplaced: vec![]
+        };
+
+        for c in compartments {
+            let parent = match c.parent {
+                Some(p) => neurites[p].clone(),
+                None => Some(Rc::clone(&soma))
+            };
+
+            let result = match (c.reason, c.neurite_type, parent) {
+                (Some(reason), _, _) => Err(reason),
+                (None, Some(neurite_type), Some(parent)) => {
+                    let model = match neurite_type {
+                        NeuriteType::Soma => self.soma_model,
+                        NeuriteType::Axon => self.axon_model,
+                        _ => self.dendrite_model
+                    };
+                    let neurite = Rc::new(RefCell::new(Neurite::new(neurite_type, model)));
+
+                    parent.borrow_mut().add_child(Rc::clone(&neurite)).map(|_| neurite)
+                }
+                (None, _, _) => Err("Parent compartment could not be placed.")
+            };
+
+            match result {
+                Ok(neurite) => {
+                    report.placed += 1;
+                    report.neurites.push(SwcPlaced {
+                        neurite: neurite.borrow().id(),
+                        neurite_type: neurite.borrow().neurite_type(),
+                        points: c.points.clone(),
+                        length: c.length,
+                        radius: c.radius / c.points.len() as f64
+                    });
+                    neurites.push(Some(neurite));
+                }
+                Err(reason) => {
+                    report.unplaced.push(SwcUnplaced {
+                        points: c.points.clone(),
+                        neurite_type: c.neurite_type,
+                        reason
+                    });
+                    neurites.push(None);
+                }
+            }
+        }
+
+        (Neuron::from_soma(soma).expect("soma neurite"), report)
+    }
+}
+
+/// Returns the proximal (or trunk) neurite type of the specified dendrite neurite type. Other neurite types are
+/// returned unchanged.
+fn proximal(neurite_type: NeuriteType) -> NeuriteType {
+    match neurite_type {
+        NeuriteType::BasalDistal => NeuriteType::BasalProximal,
+        NeuriteType::ApicalTuft => NeuriteType::ApicalTrunk,
+        t => t
+    }
+}
+
+/// Parses the points of the specified SWC text. Comment lines (starting with #) and empty lines are skipped.
+pub fn parse(text: &str) -> Result<Vec<SwcPoint>, String> {
+    let mut points = vec![];
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let error = || format!("Cannot parse SWC line {}: {}", n + 1, line);
+
+        if fields.len() < 7 {
+            return Err(error());
+        }
+
+        let f = |i: usize| fields[i].parse::<f64>().map_err(|_| error());
+
+        points.push(SwcPoint {
+            id: fields[0].parse().map_err(|_| error())?,
+            kind: fields[1].parse().map_err(|_| error())?,
+            x: f(2)?,
+            y: f(3)?,
+            z: f(4)?,
+            radius: f(5)?,
+            parent: fields[6].parse().map_err(|_| error())?
+        });
+    }
+
+    Ok(points)
+}
@@ -1 +1,16 @@
-pub static TIME_INC: f64 = 0.1;
\ No newline at end of file
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// The default simulation time increment (ms).
+pub static TIME_INC: f64 = 0.1;
+
+/// The random number generator used by stochastic simulation components.
+pub type SimRng = ChaCha8Rng;
+
+/// Returns a random number generator for the specified seed and sub-stream. Different sub-streams of the same seed
+/// produce independent sequences.
+pub fn rng_stream(seed: u64, stream: u64) -> SimRng {
+    let mut rng = SimRng::seed_from_u64(seed);
+    rng.set_stream(stream);
+    rng
+}
@@ -0,0 +1,164 @@
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+use neuron_modeler::checkpoint::Checkpoint;
+use neuron_modeler::encoding::{LatencyEncoder, RateEncoder};
+use neuron_modeler::model::ModelFormat;
+use neuron_modeler::neural::{CommandElectrode, ConductanceElectrode, ExcitatorySynapse, GapJunctionSynapse,
+                             LongTermPlasticity, Modulation, ModulationMode, NeuralModulatorySynapse, Neurite,
+                             NeuriteModVar, NeuriteType, Neuron, OrnsteinUhlenbeckElectrode, PoissonElectrode,
+                             PresynapticInput, ShortTermPlasticity, SpikeModel, SpikeTimingDependentPlasticity,
+                             TimeSeriesSensor};
+use neuron_modeler::protocol::Protocol;
+use neuron_modeler::simulation::Simulation;
+
+/// Returns a path in the temporary directory for the specified file name, unique to this test process.
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("neuron-modeler-{}-{}", std::process::id(), name)).to_string_lossy().into_owned()
+}
+
+/// Returns a simulation with the specified seed of a neuron (a soma and a dendrite) driven by a command electrode (the
+/// first electrode), a poisson input through a plastic, delayed excitatory synapse and a noisy conductance on the
+/// dendrite. The soma is coupled by a gap junction to a standalone neurite and modulates its parameter a. The
+/// standalone neurite is also returned. The electrodes are turned off.
+fn network(seed: u64) -> (Simulation, Rc<RefCell<Neurite>>) {
+    let mut sim = Simulation::new(0.1, seed);
+    let neuron = Neuron::new(SpikeModel::RegularSpiking);
+    let soma = neuron.soma();
+    let dendrite = Rc::new(RefCell::new(Neurite::new(NeuriteType::BasalProximal,
+                                                     SpikeModel::RegularSpikingPyramidalL2L3Dendrite)));
+    let target = Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, SpikeModel::FastSpiking)));
+
+    soma.borrow_mut().add_child(dendrite.clone()).unwrap();
+
+    let drive = Rc::new(RefCell::new(CommandElectrode::new(6.0)));
+    let poisson = Rc::new(RefCell::new(PoissonElectrode::new(1.0, 40.0)));
+    let noise = Rc::new(RefCell::new(OrnsteinUhlenbeckElectrode::new(0.05, 0.02, 5.0)));
+    let conductance = Rc::new(RefCell::new(ConductanceElectrode::new(noise.clone(), 0.0, &dendrite)));
+
+    soma.borrow_mut().attach_electrode(drive.clone());
+    dendrite.borrow_mut().attach_electrode(conductance.clone());
+    sim.add_electrode(drive);
+    sim.add_electrode(poisson.clone());
+    sim.add_electrode(noise);
+    sim.add_electrode(conductance);
+
+    let mut excitatory = ExcitatorySynapse::new(PresynapticInput::Electrode(poisson), soma.clone(),
+                                                ShortTermPlasticity::new(50.0, 10.0, 0.5), 5.0, 20.0);
+    excitatory.set_delay(1.5);
+    excitatory.set_ltp(Some(LongTermPlasticity::Stdp(SpikeTimingDependentPlasticity::pair(0.05, 0.06, 20.0, 20.0,
+                                                                                           0.0, 2.0))));
+
+    let gap = GapJunctionSynapse::new(PresynapticInput::Neurite(soma.clone()), target.clone(), 0.1);
+    let mut modulatory = NeuralModulatorySynapse::new(NeuriteModVar::A, PresynapticInput::Neurite(soma.clone()),
+                                                      target.clone(), ShortTermPlasticity::new(50.0, 10.0, 0.5), 20.0);
+    modulatory.set_modulation(Modulation::new(ModulationMode::Scale, 0.5, 0.0, 0.2));
+
+    let excitatory = Rc::new(RefCell::new(excitatory));
+    let gap = Rc::new(RefCell::new(gap));
+
+    soma.borrow_mut().attach_synapse(excitatory.clone()).unwrap();
+    target.borrow_mut().attach_synapse(gap.clone()).unwrap();
+    sim.add_synapse(excitatory);
+    sim.add_synapse(gap);
+    sim.add_synapse(Rc::new(RefCell::new(modulatory)));
+    sim.add_neuron(neuron);
+    sim.add_neurite(target.clone());
+
+    (sim, target)
+}
+
+/// Turns on every electrode of the specified simulation.
+fn turn_on(sim: &Simulation) {
+    for e in sim.electrodes() {
+        e.borrow_mut().on();
+    }
+}
+
+/// Adds a sine wave sensor with a rate and a latency encoder and a step protocol driving the first electrode to the
+/// specified simulation.
+fn add_inputs(sim: &mut Simulation) {
+    let samples = (0..100).map(|i| 1.0 + (i as f64 * 0.1).sin()).collect();
+    let mut sine = TimeSeriesSensor::new(samples, 1.0).unwrap();
+    sine.set_looping(true);
+
+    let sine = Rc::new(RefCell::new(sine));
+    let drive = Rc::clone(&sim.electrodes()[0]);
+
+    sim.add_sensor(sine.clone());
+    sim.add_sensor(Rc::new(RefCell::new(RateEncoder::new(sine.clone(), 50.0, 100.0))));
+    sim.add_sensor(Rc::new(RefCell::new(LatencyEncoder::new(sine, 20.0, 2.0))));
+    sim.add_protocol(&Protocol::fi_steps(4, 100.0, 20.0, 50.0, 4.0, 2.0), drive);
+}
+
+#[test]
+fn restored_simulation_continues_bit_exactly() {
+    for (format, name) in [(ModelFormat::Ron, "restore.ron"), (ModelFormat::Binary, "restore.bin")] {
+        let (mut sim, _) = network(13);
+        let model = temp_path(&format!("model-{}", name));
+        let path = temp_path(name);
+
+        sim.save_model(&model, format).unwrap();
+        add_inputs(&mut sim);
+        turn_on(&sim);
+        sim.run_for(150.0);
+        sim.checkpoint().unwrap().save(&path, format).unwrap();
+        sim.run_for(150.0);
+
+        // A simulation of the same model and inputs, with a different seed, continues from the checkpoint
+        let mut restored = Simulation::load_model(&model, format, 0.1, 14).unwrap();
+        add_inputs(&mut restored);
+        restored.restore(&Checkpoint::load(&path, format).unwrap()).unwrap();
+        assert_eq!(restored.time(), 150.0);
+        restored.run_for(150.0);
+
+        assert_eq!(sim.checkpoint().unwrap(), restored.checkpoint().unwrap(), "{:?}", format);
+        fs::remove_file(&model).unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn restoring_rewinds_the_simulation() {
+    let (mut sim, target) = network(15);
+
+    add_inputs(&mut sim);
+    turn_on(&sim);
+    sim.run_for(100.0);
+
+    let checkpoint = sim.checkpoint().unwrap();
+    let parameters = target.borrow().parameters();
+
+    sim.run_for(100.0);
+    let end = sim.checkpoint().unwrap();
+    assert_ne!(end, checkpoint);
+
+    sim.restore(&checkpoint).unwrap();
+    assert_eq!(sim.checkpoint().unwrap(), checkpoint);
+    assert_eq!(target.borrow().parameters(), parameters);
+
+    sim.run_for(100.0);
+    assert_eq!(sim.checkpoint().unwrap(), end);
+}
+
+#[test]
+fn mismatched_checkpoints_are_rejected() {
+    let (mut sim, _) = network(17);
+    let (other, _) = network(17);
+    let checkpoint = other.checkpoint().unwrap();
+
+    assert!(sim.restore(&checkpoint).is_err());
+
+    let mut other = Simulation::new(0.05, 17);
+    assert!(other.restore(&sim.checkpoint().unwrap()).is_err());
+}
+
+#[test]
+fn json_checkpoints_are_rejected() {
+    let (sim, _) = network(19);
+    let path = temp_path("checkpoint.json");
+
+    assert!(sim.checkpoint().unwrap().save(&path, ModelFormat::Json).is_err());
+    assert!(Checkpoint::load(&path, ModelFormat::Json).is_err());
+    assert!(fs::metadata(&path).is_err());
+}
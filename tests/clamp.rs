@@ -0,0 +1,117 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use neuron_modeler::neural::{CommandElectrode, ConductanceElectrode, Electrode, Neurite, NeuriteType, SpikeModel,
+                             VoltageClampElectrode};
+
+/// Returns a regular spiking soma clamped by a voltage clamp electrode with the specified holding potential, with a
+/// step command to -80 mV from 20 ms to 40 ms.
+fn clamped(v: f64) -> (Rc<RefCell<Neurite>>, Rc<RefCell<VoltageClampElectrode>>) {
+    let neurite = Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, SpikeModel::RegularSpiking)));
+    let clamp = Rc::new(RefCell::new(VoltageClampElectrode::new(&neurite, v, 5.0)));
+
+    clamp.borrow_mut().add_command(20.0, 20.0, -80.0);
+    clamp.borrow_mut().on();
+    neurite.borrow_mut().attach_electrode(clamp.clone());
+
+    (neurite, clamp)
+}
+
+/// Processes the specified clamped neurite for the specified number of 0.1 ms time steps and returns its membrane
+/// potential on every step.
+fn run(neurite: &Rc<RefCell<Neurite>>, clamp: &Rc<RefCell<VoltageClampElectrode>>, steps: usize) -> Vec<f64> {
+    (0..steps).map(|_| {
+        clamp.borrow_mut().process(0.1);
+        neurite.borrow_mut().process(0.1).0
+    }).collect()
+}
+
+#[test]
+fn clamp_holds_and_steps_the_membrane_potential() {
+    let (neurite, clamp) = clamped(-60.0);
+    let v = run(&neurite, &clamp, 600);
+
+    assert!((v[199] + 60.0).abs() < 3.0, "{}", v[199]);
+    assert!((v[399] + 80.0).abs() < 3.0, "{}", v[399]);
+    assert!((v[599] + 60.0).abs() < 3.0, "{}", v[599]);
+    assert_eq!(clamp.borrow().command(30.0), -80.0);
+    assert_eq!(clamp.borrow().command(40.0), -60.0);
+}
+
+#[test]
+fn clamp_current_opposes_the_command_step() {
+    let (neurite, clamp) = clamped(-60.0);
+
+    run(&neurite, &clamp, 250);
+    let hyperpolarizing = clamp.borrow().output();
+    run(&neurite, &clamp, 200);
+
+    // Holding below rest takes an outward (negative) current that is larger than at the holding potential
+    assert!(hyperpolarizing < 0.0 && hyperpolarizing < clamp.borrow().output(), "{} {}", hyperpolarizing,
+            clamp.borrow().output());
+}
+
+#[test]
+fn clamp_currents_are_recorded_only_when_set() {
+    let (neurite, clamp) = clamped(-60.0);
+
+    run(&neurite, &clamp, 10);
+    assert!(clamp.borrow().clamp_currents().is_empty());
+
+    clamp.borrow_mut().set_recording(true);
+    run(&neurite, &clamp, 10);
+
+    let currents = clamp.borrow().clamp_currents().to_vec();
+    assert_eq!(currents.len(), 10);
+    assert_eq!(currents.last().unwrap().1, clamp.borrow().output());
+}
+
+#[test]
+fn clamp_of_a_dropped_neurite_injects_nothing() {
+    let (neurite, clamp) = clamped(-60.0);
+
+    run(&neurite, &clamp, 10);
+    drop(neurite);
+
+    assert_eq!(clamp.borrow_mut().process(0.1), 0.0);
+}
+
+#[test]
+fn dynamic_clamp_injects_conductance_times_driving_force() {
+    // The soma is not processed, so its membrane potential stays at its initial value
+    let neurite = Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, SpikeModel::RegularSpiking)));
+    let v = neurite.borrow().v();
+    let g = Rc::new(RefCell::new(CommandElectrode::new(0.5)));
+    let mut clamp = ConductanceElectrode::new(g.clone(), 0.0, &neurite);
+
+    clamp.on();
+    g.borrow_mut().process(0.1);
+    assert_eq!(clamp.process(0.1), 0.5 * (0.0 - v));
+
+    clamp.set_voltage(v);
+    assert_eq!(clamp.process(0.1), 0.0);
+
+    // Turning the electrode off also turns its conductance electrode off
+    clamp.off();
+    assert_eq!(g.borrow_mut().process(0.1), 0.0);
+    assert_eq!(clamp.process(0.1), 0.0);
+}
+
+#[test]
+fn dynamic_clamp_conductance_drives_the_neurite() {
+    let neurite = Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, SpikeModel::RegularSpiking)));
+    let control = Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, SpikeModel::RegularSpiking)));
+    let g = Rc::new(RefCell::new(CommandElectrode::new(0.1)));
+    let clamp = Rc::new(RefCell::new(ConductanceElectrode::new(g.clone(), 0.0, &neurite)));
+
+    clamp.borrow_mut().on();
+    neurite.borrow_mut().attach_electrode(clamp.clone());
+
+    for _ in 0..50 {
+        g.borrow_mut().process(0.1);
+        clamp.borrow_mut().process(0.1);
+        neurite.borrow_mut().process(0.1);
+        control.borrow_mut().process(0.1);
+    }
+
+    assert!(neurite.borrow().v() > control.borrow().v(), "{} {}", neurite.borrow().v(), control.borrow().v());
+}
@@ -0,0 +1,90 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use neuron_modeler::neural::{Electrode, ExcitatorySynapse, Neurite, NeuriteType, Neuron, PresynapticInput,
+                             PulseElectrode, ShortTermPlasticity, SpikeDelay, SpikeModel, Synapse};
+
+#[test]
+fn spikes_are_delivered_after_the_delay() {
+    let mut delay = SpikeDelay::new(2.0);
+    let delivered: Vec<bool> = (0..10).map(|t| delay.process(t == 0 || t == 1, 0.5)).collect();
+
+    assert_eq!(delivered, [false, false, false, false, true, true, false, false, false, false]);
+    assert_eq!(delay.pending(), 0);
+}
+
+#[test]
+fn spikes_in_transit_are_queued() {
+    let mut delay = SpikeDelay::new(5.0);
+
+    for t in 0..4 {
+        assert!(!delay.process(true, 1.0));
+        assert_eq!(delay.pending(), t + 1);
+    }
+
+    assert!(!delay.process(false, 1.0));
+    assert!(delay.process(false, 1.0));
+    assert_eq!(delay.pending(), 3);
+}
+
+#[test]
+fn zero_and_negative_delays_deliver_at_once() {
+    for d in [0.0, -1.0] {
+        let mut delay = SpikeDelay::new(d);
+
+        assert_eq!(delay.delay(), 0.0);
+        assert!(delay.process(true, 0.1));
+        assert!(!delay.process(false, 0.1));
+    }
+}
+
+#[test]
+fn synapse_receives_spikes_after_the_delay() {
+    let post = Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, SpikeModel::RegularSpiking)));
+    let synapse = |pre: &Rc<RefCell<PulseElectrode>>, delay: f64| {
+        let mut synapse = ExcitatorySynapse::new(PresynapticInput::Electrode(pre.clone()), post.clone(),
+                                                 ShortTermPlasticity::new(50.0, 10.0, 0.5), 5.0, 1.0);
+        synapse.set_delay(delay);
+        synapse
+    };
+
+    // The reference synapse never receives a spike, so the other synapses follow it until their spike arrives
+    let silent = Rc::new(RefCell::new(PulseElectrode::new(1.0, 0.5)));
+    let pre = Rc::new(RefCell::new(PulseElectrode::new(1.0, 0.5)));
+    let mut reference = synapse(&silent, 3.0);
+    let mut direct = synapse(&pre, 0.0);
+    let mut delayed = synapse(&pre, 3.0);
+
+    for t in 0..60 {
+        if t == 50 {
+            pre.borrow_mut().on();
+        }
+
+        pre.borrow_mut().process(1.0);
+        reference.process(1.0);
+        direct.process(1.0);
+        delayed.process(1.0);
+
+        assert_eq!(direct.input() != reference.input(), t >= 50, "step {}", t);
+        assert_eq!(delayed.input() != reference.input(), t >= 53, "step {}", t);
+    }
+}
+
+#[test]
+fn axon_delay_counts_the_axon_neurites_on_the_path() {
+    let neuron = Neuron::new(SpikeModel::RegularSpiking);
+    let dendrite = Rc::new(RefCell::new(Neurite::new(NeuriteType::BasalProximal, SpikeModel::RegularSpiking)));
+    let axon = Rc::new(RefCell::new(Neurite::new(NeuriteType::Axon, SpikeModel::RegularSpiking)));
+    let terminal = Rc::new(RefCell::new(Neurite::new(NeuriteType::Axon, SpikeModel::RegularSpiking)));
+
+    neuron.soma().borrow_mut().add_child(dendrite.clone()).unwrap();
+    neuron.soma().borrow_mut().add_child(axon.clone()).unwrap();
+    axon.borrow_mut().add_child(terminal.clone()).unwrap();
+
+    assert_eq!(neuron.axon_delay(&neuron.soma(), 0.5), Some(0.0));
+    assert_eq!(neuron.axon_delay(&dendrite, 0.5), Some(0.0));
+    assert_eq!(neuron.axon_delay(&axon, 0.5), Some(0.5));
+    assert_eq!(neuron.axon_delay(&terminal, 0.5), Some(1.0));
+
+    let other = Rc::new(RefCell::new(Neurite::new(NeuriteType::Axon, SpikeModel::RegularSpiking)));
+    assert_eq!(neuron.axon_delay(&other, 0.5), None);
+}
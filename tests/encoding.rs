@@ -0,0 +1,114 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use neuron_modeler::encoding::{DeltaEncoder, LatencyEncoder, Polarity, RateEncoder, TuningCurveEncoder};
+use neuron_modeler::neural::{RateSensor, Sensor};
+use neuron_modeler::system::rng_stream;
+
+/// Returns an analog source sensor that outputs the value of the returned variable.
+fn source(x: f64) -> (Rc<Cell<f64>>, Rc<RefCell<RateSensor>>) {
+    let var = Rc::new(Cell::new(x));
+    let sensor = Rc::new(RefCell::new(RateSensor::new(Rc::clone(&var), 1.0, f64::INFINITY)));
+
+    (var, sensor)
+}
+
+/// Returns the output of the specified encoder on each time step (ms) after processing its source.
+fn spikes(source: &Rc<RefCell<RateSensor>>, encoder: &mut dyn Sensor, steps: usize, time_inc: f64) -> Vec<f64> {
+    (0..steps)
+        .map(|_| {
+            source.borrow_mut().process(time_inc);
+            encoder.process(time_inc)
+        })
+        .collect()
+}
+
+#[test]
+fn rate_encoder_spikes_at_the_clamped_rate() {
+    let (_, sensor) = source(2.0);
+    let mut encoder = RateEncoder::new(sensor.clone(), 100.0, 50.0);
+    encoder.seed(rng_stream(7, 0));
+
+    // 50 Hz over 100 s, within 4 standard deviations
+    let count: f64 = spikes(&sensor, &mut encoder, 100_000, 1.0).iter().sum();
+    assert!((count - 5000.0).abs() < 4.0 * (5000.0f64 * 0.95).sqrt(), "{} spikes", count);
+}
+
+#[test]
+fn rate_encoder_is_reproducible_from_its_seed() {
+    let (_, sensor) = source(1.0);
+    let mut a = RateEncoder::new(sensor.clone(), 100.0, 1000.0);
+    let mut b = RateEncoder::new(sensor.clone(), 100.0, 1000.0);
+    a.seed(rng_stream(3, 1));
+    b.seed(rng_stream(3, 1));
+
+    assert_eq!(spikes(&sensor, &mut a, 1000, 1.0), spikes(&sensor, &mut b, 1000, 1.0));
+}
+
+#[test]
+fn latency_encoder_spikes_once_per_window() {
+    let (var, sensor) = source(0.5);
+    let mut encoder = LatencyEncoder::new(sensor.clone(), 10.0, 1.0);
+    let y = spikes(&sensor, &mut encoder, 30, 1.0);
+
+    let times: Vec<usize> = (0..30).filter(|&t| y[t] > 0.0).collect();
+    assert_eq!(times, [5, 15, 25]);
+
+    // Larger values spike earlier, and values at or below 0 do not spike
+    var.set(1.0);
+    assert_eq!(spikes(&sensor, &mut encoder, 10, 1.0)[0], 1.0);
+    var.set(0.0);
+    assert!(spikes(&sensor, &mut encoder, 30, 1.0).iter().all(|&y| y == 0.0));
+}
+
+#[test]
+fn tuning_curve_population_prefers_the_nearest_encoder() {
+    let (_, sensor) = source(0.75);
+    let population = TuningCurveEncoder::population(sensor.clone(), 5, 0.0, 1.0, 0.1, 200.0);
+    let mut counts = vec![0.0; population.len()];
+
+    for (i, e) in population.iter().enumerate() {
+        e.borrow_mut().seed(rng_stream(11, i as u64));
+    }
+
+    for _ in 0..10_000 {
+        sensor.borrow_mut().process(1.0);
+
+        for (count, e) in counts.iter_mut().zip(&population) {
+            *count += e.borrow_mut().process(1.0);
+        }
+    }
+
+    // Encoder 3 prefers 0.75, and the encoders at 0.5 and 1.0 are 2.5 tuning widths away
+    assert!(counts[3] > 10.0 * counts[2] && counts[3] > 10.0 * counts[4], "{:?}", counts);
+    assert_eq!(counts[0], 0.0);
+}
+
+#[test]
+fn delta_encoder_spikes_on_threshold_crossings() {
+    let values = [0.0, 0.5, 1.2, 1.4, 3.1, 0.9];
+    let expected = [
+        (Polarity::Both, [0.0, 0.0, 1.0, 0.0, 1.0, 1.0]),
+        (Polarity::On, [0.0, 0.0, 1.0, 0.0, 1.0, 0.0]),
+        (Polarity::Off, [0.0, 0.0, 0.0, 0.0, 0.0, 1.0])
+    ];
+
+    for (polarity, expected) in expected {
+        let (var, sensor) = source(0.0);
+        let mut encoder = DeltaEncoder::new(sensor.clone(), 1.0, polarity).unwrap();
+
+        for (&x, &y) in values.iter().zip(&expected) {
+            var.set(x);
+            sensor.borrow_mut().process(1.0);
+            assert_eq!(encoder.process(1.0), y, "{:?} at {}", polarity, x);
+        }
+    }
+}
+
+#[test]
+fn delta_encoder_rejects_invalid_thresholds() {
+    let (_, sensor) = source(0.0);
+
+    for th in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(DeltaEncoder::new(sensor.clone(), th, Polarity::Both).is_err(), "{}", th);
+    }
+}
@@ -0,0 +1,162 @@
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+use neuron_modeler::export::{write_npy, write_spikes_csv, write_spikes_npz, write_trace_csv, write_trace_npy,
+                             write_trace_npz, Metadata};
+use neuron_modeler::neural::{CommandElectrode, Electrode, NeuriteVar, Neuron, SpikeModel};
+use neuron_modeler::recording::{BufferMode, ProbeTarget, ProbeVar};
+use neuron_modeler::simulation::Simulation;
+
+/// Returns a path in the temporary directory for the specified file name, unique to this test process.
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("neuron-modeler-{}-{}", std::process::id(), name)).to_string_lossy().into_owned()
+}
+
+/// Returns a simulation of a driven tonic spiking neuron, with a probe (0) of its membrane potential and spike output
+/// and a spike recorder (0), after 100 ms.
+fn recorded() -> Simulation {
+    let mut sim = Simulation::new(0.1, 1);
+    let neuron = Neuron::new(SpikeModel::TonicSpiking);
+    let electrode = Rc::new(RefCell::new(CommandElectrode::new(14.0)));
+    let id = neuron.soma().borrow().id();
+
+    electrode.borrow_mut().on();
+    neuron.soma().borrow_mut().attach_electrode(electrode.clone());
+    sim.add_electrode(electrode);
+    sim.add_neuron(neuron);
+    sim.add_probe(ProbeTarget::Neurite(id), vec![ProbeVar::Neurite(NeuriteVar::V), ProbeVar::Neurite(NeuriteVar::Y)],
+                  1.0, BufferMode::Unbounded).unwrap();
+    sim.add_spike_recorder(&[id]).unwrap();
+    sim.run_for(100.0);
+
+    sim
+}
+
+/// Returns the header and the little-endian f64 data of the specified NumPy .npy bytes.
+fn npy_f64(bytes: &[u8]) -> (String, Vec<f64>) {
+    assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+
+    let len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    let header = String::from_utf8(bytes[10..10 + len].to_vec()).unwrap();
+    let data = bytes[10 + len..].chunks(8).map(|c| f64::from_le_bytes(c.try_into().unwrap())).collect();
+
+    assert_eq!((10 + len) % 64, 0);
+    assert!(header.ends_with('\n'));
+
+    (header, data)
+}
+
+#[test]
+fn trace_csv_has_metadata_header_and_samples() {
+    let sim = recorded();
+    let probe = sim.probe(0).unwrap();
+    let path = temp_path("trace.csv");
+
+    write_trace_csv(&path, probe, &Metadata::from_probe(probe, sim.time_inc())).unwrap();
+    let text = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let mut lines = text.lines().skip_while(|l| l.starts_with('#'));
+    assert!(text.starts_with("# time_inc: 0.1\n# source: neurite\n"));
+    assert_eq!(lines.next(), Some("time,v,y"));
+
+    let rows: Vec<Vec<f64>> = lines.map(|l| l.split(',').map(|x| x.parse().unwrap()).collect()).collect();
+    assert_eq!(rows.len(), probe.times().len());
+
+    for ((row, &t), sample) in rows.iter().zip(probe.times()).zip(probe.samples()) {
+        assert_eq!(row[0], t);
+        assert_eq!(&row[1..], &sample[..]);
+    }
+}
+
+#[test]
+fn spikes_csv_has_one_row_per_spike() {
+    let sim = recorded();
+    let recorder = sim.spike_recorder(0).unwrap();
+    let path = temp_path("spikes.csv");
+
+    assert!(!recorder.events().is_empty());
+
+    write_spikes_csv(&path, recorder, &Metadata::from_spike_recorder(recorder, sim.time_inc())).unwrap();
+    let text = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let rows: Vec<&str> = text.lines().skip_while(|l| l.starts_with('#')).skip(1).collect();
+    assert_eq!(rows.len(), recorder.events().len());
+
+    for (row, (id, t)) in rows.iter().zip(recorder.events()) {
+        assert_eq!(*row, format!("{},{}", id.uuid(), t));
+    }
+}
+
+#[test]
+fn trace_npy_holds_time_and_variables_per_row() {
+    let sim = recorded();
+    let probe = sim.probe(0).unwrap();
+    let path = temp_path("trace.npy");
+
+    write_trace_npy(&path, probe).unwrap();
+    let (header, data) = npy_f64(&fs::read(&path).unwrap());
+    fs::remove_file(&path).unwrap();
+
+    assert!(header.contains(&format!("'shape': ({}, 3)", probe.times().len())));
+    assert!(header.contains("'descr': '<f8'"));
+
+    for ((row, &t), sample) in data.chunks(3).zip(probe.times()).zip(probe.samples()) {
+        assert_eq!(row, [t, sample[0], sample[1]]);
+    }
+}
+
+#[test]
+fn npy_rejects_a_mismatched_shape() {
+    let path = temp_path("mismatch.npy");
+
+    assert!(write_npy(&path, &[1.0, 2.0, 3.0], &[2, 2]).is_err());
+    assert!(fs::metadata(&path).is_err());
+}
+
+#[test]
+fn npz_archives_hold_named_arrays() {
+    let sim = recorded();
+    let probe = sim.probe(0).unwrap();
+    let recorder = sim.spike_recorder(0).unwrap();
+    let trace = temp_path("trace.npz");
+    let spikes = temp_path("spikes.npz");
+
+    write_trace_npz(&trace, probe, &Metadata::from_probe(probe, sim.time_inc())).unwrap();
+    write_spikes_npz(&spikes, recorder, &Metadata::from_spike_recorder(recorder, sim.time_inc())).unwrap();
+
+    let voltages: Vec<f64> = probe.samples().iter().map(|s| s[0]).collect();
+    let times: Vec<f64> = recorder.events().iter().map(|(_, t)| *t).collect();
+
+    let arrays = [
+        (&trace, ["time.npy", "v.npy", "y.npy", "metadata.npy"], voltages),
+        (&spikes, ["neurite_ids.npy", "spike_times.npy", "spike_index.npy", "metadata.npy"], times)
+    ];
+
+    for (path, names, data) in arrays {
+        let mut archive = zip::ZipArchive::new(fs::File::open(path).unwrap()).unwrap();
+        let found: Vec<&str> = archive.file_names().collect();
+
+        assert_eq!(found.len(), names.len());
+        assert!(names.iter().all(|name| found.contains(name)), "{:?}", found);
+
+        // The second array is the membrane potential or the spike times
+        let mut bytes = vec![];
+        std::io::Read::read_to_end(&mut archive.by_name(names[1]).unwrap(), &mut bytes).unwrap();
+        assert_eq!(npy_f64(&bytes).1, data);
+
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn npz_rejects_a_variable_recorded_twice() {
+    let mut sim = recorded();
+    let id = sim.neurons()[0].soma().borrow().id();
+    let index = sim.add_probe(ProbeTarget::Neurite(id), vec![ProbeVar::Neurite(NeuriteVar::V); 2], 1.0,
+                              BufferMode::Unbounded).unwrap();
+    let probe = sim.probe(index).unwrap();
+
+    assert!(write_trace_npz(&temp_path("twice.npz"), probe, &Metadata::new(sim.time_inc())).is_err());
+}
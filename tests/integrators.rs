@@ -0,0 +1,93 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use neuron_modeler::neural::{CommandElectrode, Electrode, Integrator, Neurite, NeuriteType, SpikeModel};
+
+/// Returns a tonic spiking soma neurite with the specified integrator, driven by a command electrode with the specified
+/// current.
+fn driven(integrator: Integrator, current: f64) -> (Rc<RefCell<Neurite>>, Rc<RefCell<CommandElectrode>>) {
+    let neurite = Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, SpikeModel::TonicSpiking)));
+    let electrode = Rc::new(RefCell::new(CommandElectrode::new(current)));
+
+    electrode.borrow_mut().on();
+    neurite.borrow_mut().set_integrator(integrator);
+    neurite.borrow_mut().attach_electrode(electrode.clone());
+
+    (neurite, electrode)
+}
+
+/// Returns the spike times (ms) of a driven neurite over the specified length of time (ms), including the spike offset
+/// within each time step.
+fn spike_times(integrator: Integrator, time_inc: f64, ms: f64) -> Vec<f64> {
+    let (neurite, electrode) = driven(integrator, 14.0);
+    let steps = (ms / time_inc).round() as usize;
+    let mut spikes = vec![];
+
+    for step in 0..steps {
+        electrode.borrow_mut().process(time_inc);
+        let (v, y) = neurite.borrow_mut().process(time_inc);
+
+        assert!(v.is_finite());
+
+        if y > 0.0 {
+            spikes.push(step as f64 * time_inc + neurite.borrow().spike_offset());
+        }
+    }
+
+    spikes
+}
+
+#[test]
+fn runge_kutta_matches_a_fine_reference() {
+    let reference = spike_times(Integrator::RK4, 0.001, 100.0);
+    assert!(reference.len() >= 3);
+
+    for (integrator, time_inc, tol) in [(Integrator::RK4, 0.1, 0.05), (Integrator::RK45(1.0e-6), 1.0, 0.05),
+                                        (Integrator::Euler, 0.01, 0.5)] {
+        let spikes = spike_times(integrator, time_inc, 100.0);
+
+        assert_eq!(spikes.len(), reference.len(), "{:?}", integrator);
+
+        for (t, r) in spikes.iter().zip(&reference) {
+            assert!((t - r).abs() < tol, "{:?}: spike at {} ms, reference {} ms", integrator, t, r);
+        }
+    }
+}
+
+#[test]
+fn spike_offset_is_within_the_time_step() {
+    for integrator in [Integrator::Euler, Integrator::HalfStep, Integrator::RK4, Integrator::RK45(1.0e-3)] {
+        let (neurite, electrode) = driven(integrator, 14.0);
+        let mut spikes = 0;
+
+        for _ in 0..1000 {
+            electrode.borrow_mut().process(0.5);
+            let (_, y) = neurite.borrow_mut().process(0.5);
+            let offset = neurite.borrow().spike_offset();
+
+            if y > 0.0 {
+                spikes += 1;
+                assert!((0.0..=0.5).contains(&offset), "{:?}: offset {}", integrator, offset);
+            }
+            else {
+                assert_eq!(offset, 0.0);
+            }
+        }
+
+        assert!(spikes > 0, "{:?}", integrator);
+    }
+}
+
+#[test]
+fn strong_input_spikes_at_most_once_per_step() {
+    for integrator in [Integrator::Euler, Integrator::HalfStep, Integrator::RK4, Integrator::RK45(1.0e-3)] {
+        let (neurite, electrode) = driven(integrator, 1.0e4);
+
+        for _ in 0..200 {
+            electrode.borrow_mut().process(1.0);
+            let (v, y) = neurite.borrow_mut().process(1.0);
+
+            assert!(v.is_finite(), "{:?}", integrator);
+            assert!(y == 0.0 || y == 1.0, "{:?}", integrator);
+        }
+    }
+}
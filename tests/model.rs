@@ -0,0 +1,163 @@
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+use neuron_modeler::model::{ModelFile, ModelFormat};
+use neuron_modeler::neural::{CommandElectrode, ExcitatorySynapse, GapJunctionSynapse, Modulation,
+                             ModulationMode, NeuralModulatorySynapse, Neurite, NeuriteModVar, NeuriteType, NeuriteVar,
+                             PoissonElectrode, PresynapticInput, ShortTermPlasticity, SpikeModel};
+use neuron_modeler::recording::{BufferMode, ProbeTarget, ProbeVar};
+use neuron_modeler::simulation::Simulation;
+
+/// Returns a path in the temporary directory for the specified file name, unique to this test process.
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("neuron-modeler-{}-{}", std::process::id(), name)).to_string_lossy().into_owned()
+}
+
+/// Returns a simulation with the specified seed of a soma driven by a command electrode and a poisson input through a
+/// delayed excitatory synapse. The soma is coupled by a gap junction to a standalone neurite and modulates its
+/// parameter a. The standalone neurite is also returned.
+fn network(seed: u64) -> (Simulation, Rc<RefCell<Neurite>>) {
+    let mut sim = Simulation::new(0.1, seed);
+    let soma = Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, SpikeModel::RegularSpiking)));
+    let target = Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, SpikeModel::FastSpiking)));
+    let drive = Rc::new(RefCell::new(CommandElectrode::new(6.0)));
+    let poisson = Rc::new(RefCell::new(PoissonElectrode::new(1.0, 40.0)));
+
+    let mut excitatory = ExcitatorySynapse::new(PresynapticInput::Electrode(poisson.clone()), soma.clone(),
+                                                ShortTermPlasticity::new(50.0, 10.0, 0.5), 5.0, 20.0);
+    excitatory.set_delay(1.5);
+
+    let gap = GapJunctionSynapse::new(PresynapticInput::Neurite(soma.clone()), target.clone(), 0.1);
+    let mut modulatory = NeuralModulatorySynapse::new(NeuriteModVar::A, PresynapticInput::Neurite(soma.clone()),
+                                                      target.clone(), ShortTermPlasticity::new(50.0, 10.0, 0.5), 20.0);
+    modulatory.set_modulation(Modulation::new(ModulationMode::Scale, 0.5, 0.0, 0.2));
+
+    let excitatory = Rc::new(RefCell::new(excitatory));
+    let gap = Rc::new(RefCell::new(gap));
+
+    soma.borrow_mut().attach_electrode(drive.clone());
+    soma.borrow_mut().attach_synapse(excitatory.clone()).unwrap();
+    target.borrow_mut().attach_synapse(gap.clone()).unwrap();
+    sim.add_electrode(drive);
+    sim.add_electrode(poisson);
+    sim.add_synapse(excitatory);
+    sim.add_synapse(gap);
+    sim.add_synapse(Rc::new(RefCell::new(modulatory)));
+    sim.add_neurite(soma);
+    sim.add_neurite(target.clone());
+
+    (sim, target)
+}
+
+/// Turns on every electrode of the specified simulation.
+fn turn_on(sim: &Simulation) {
+    for e in sim.electrodes() {
+        e.borrow_mut().on();
+    }
+}
+
+#[test]
+fn saving_a_loaded_model_gives_the_same_file() {
+    for (format, name) in [(ModelFormat::Ron, "resave.ron"), (ModelFormat::Binary, "resave.bin")] {
+        let (sim, _) = network(3);
+        let path = temp_path(name);
+
+        sim.save_model(&path, format).unwrap();
+        let saved = fs::read(&path).unwrap();
+
+        Simulation::load_model(&path, format, 0.1, 3).unwrap().save_model(&path, format).unwrap();
+        assert!(fs::read(&path).unwrap() == saved, "{:?}", format);
+        fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn loaded_model_runs_like_the_original() {
+    for (format, name) in [(ModelFormat::Ron, "run.ron"), (ModelFormat::Binary, "run.bin")] {
+        let (mut sim, target) = network(5);
+        let path = temp_path(name);
+        let id = target.borrow().id();
+
+        sim.save_model(&path, format).unwrap();
+        let mut loaded = Simulation::load_model(&path, format, 0.1, 5).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let traces: Vec<Vec<f64>> = [&mut sim, &mut loaded].into_iter().map(|sim| {
+            let probe = sim.add_probe(ProbeTarget::Neurite(id), vec![ProbeVar::Neurite(NeuriteVar::V)], 0.1,
+                                      BufferMode::Unbounded).unwrap();
+
+            turn_on(sim);
+            sim.run_for(200.0);
+            sim.probe(probe).unwrap().trace(ProbeVar::Neurite(NeuriteVar::V)).unwrap()
+        }).collect();
+
+        assert!(traces[0] == traces[1], "{:?}", format);
+    }
+}
+
+#[test]
+fn json_model_loads() {
+    let (sim, target) = network(7);
+    let path = temp_path("model.json");
+
+    sim.save_model(&path, ModelFormat::Json).unwrap();
+    let model = ModelFile::load(&path, ModelFormat::Json).unwrap().build().unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(model.neurites.len(), 2);
+    assert_eq!(model.synapses.len(), 3);
+    assert_eq!(model.electrodes.len(), 2);
+    assert_eq!(model.neurites[1].borrow().id(), target.borrow().id());
+    assert_eq!(model.neurites[1].borrow().synapses().len(), 1);
+}
+
+#[test]
+fn model_saves_parameters_before_modulation() {
+    let (mut sim, target) = network(9);
+    let base = Neurite::new(NeuriteType::Soma, SpikeModel::FastSpiking).parameters();
+    let path = temp_path("modulated.ron");
+
+    turn_on(&sim);
+    sim.run_for(200.0);
+    assert_ne!(target.borrow().parameters()[0], base[0]);
+
+    sim.save_model(&path, ModelFormat::Ron).unwrap();
+    let model = ModelFile::load(&path, ModelFormat::Ron).unwrap().build().unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(model.neurites[1].borrow().parameters(), base);
+}
+
+#[test]
+fn gap_junctions_must_not_have_short_term_plasticity() {
+    let (sim, _) = network(11);
+    let path = temp_path("gap.json");
+
+    sim.save_model(&path, ModelFormat::Json).unwrap();
+
+    // Give the gap junction the short-term plasticity data of the excitatory synapse
+    let mut json: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+    let synapses = json["synapse_records"].as_array_mut().unwrap();
+    let stp = synapses.iter().find(|s| s["syn_type"] == "Excitatory").unwrap()["stp"].clone();
+    let gap = synapses.iter_mut().find(|s| s["syn_type"] == "Gap").unwrap();
+
+    assert!(gap["stp"].is_null() && !stp.is_null());
+    gap["stp"] = stp;
+    fs::write(&path, serde_json::to_vec(&json).unwrap()).unwrap();
+
+    assert!(ModelFile::load(&path, ModelFormat::Json).unwrap().build().is_err());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn newer_model_versions_are_rejected() {
+    let (sim, _) = network(13);
+    let path = temp_path("version.ron");
+
+    sim.save_model(&path, ModelFormat::Ron).unwrap();
+    let text = fs::read_to_string(&path).unwrap().replacen("version: 1,", "version: 2,", 1);
+    fs::write(&path, text).unwrap();
+
+    assert!(ModelFile::load(&path, ModelFormat::Ron).is_err());
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,83 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use neuron_modeler::neural::{Electrode, ExcitatorySynapse, Modulation, ModulationMode, NeuralModulatorySynapse,
+                             Neurite, NeuriteModVar, NeuriteType, PresynapticInput, PulseElectrode, ShortTermPlasticity,
+                             SpikeModel, Synapse, SynapticModVar, SynapticModulatorySynapse};
+
+/// Returns a new presynaptic pulse electrode.
+fn electrode() -> Rc<RefCell<PulseElectrode>> {
+    Rc::new(RefCell::new(PulseElectrode::new(10.0, 5.0)))
+}
+
+/// Processes the specified electrode for 12 ms, with a pulse starting at 10 ms, calling the specified function after
+/// every time step.
+fn pulse(electrode: &Rc<RefCell<PulseElectrode>>, mut step: impl FnMut()) {
+    for i in 0..120 {
+        if i == 100 {
+            electrode.borrow_mut().on();
+        }
+
+        electrode.borrow_mut().process(0.1);
+        step();
+    }
+}
+
+#[test]
+fn modulation_scales_or_shifts_within_bounds() {
+    let scale = Modulation::new(ModulationMode::Scale, 0.5, 0.0, 10.0);
+    let shift = Modulation::new(ModulationMode::Shift, -2.0, -5.0, 10.0);
+
+    assert_eq!(scale.apply(4.0, 2.0), 8.0);
+    assert_eq!(scale.apply(4.0, 8.0), 10.0);
+    assert_eq!(shift.apply(4.0, 1.5), 1.0);
+    assert_eq!(shift.apply(4.0, 10.0), -5.0);
+}
+
+#[test]
+fn synaptic_modulator_scales_only_its_target_variable() {
+    let pre = electrode();
+    let post = Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, SpikeModel::Accommodation)));
+    let stp = || ShortTermPlasticity::new(50.0, 10.0, 0.5);
+    let input = || PresynapticInput::Electrode(pre.clone());
+    let modulator = Rc::new(RefCell::new(SynapticModulatorySynapse::new(SynapticModVar::X, input(), post.clone(),
+                                                                        stp(), 20.0)));
+    let mut plain = ExcitatorySynapse::new(input(), post.clone(), stp(), 5.0, 1.0);
+    let mut modulated = plain.clone();
+
+    modulated.set_mod_syn(Some(modulator.clone()));
+
+    pulse(&pre, || {
+        modulator.borrow_mut().process(0.1);
+        plain.process(0.1);
+        modulated.process(0.1);
+    });
+
+    let level = modulator.borrow().input();
+    assert!(level > 0.0 && plain.input() > 0.0);
+    assert_eq!(modulator.borrow().modulate(SynapticModVar::W, 2.0), 2.0);
+    assert!((modulated.input() - plain.input() * (1.0 + level)).abs() < 1.0e-12);
+}
+
+#[test]
+fn neural_modulation_starts_from_the_base_value_until_unmodulated() {
+    let pre = electrode();
+    let post = Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, SpikeModel::Accommodation)));
+    let base = post.borrow().mod_var(NeuriteModVar::A);
+    let modulation = Modulation::new(ModulationMode::Scale, 2.0, 0.0, 1.0);
+    let mut syn = NeuralModulatorySynapse::new(NeuriteModVar::A, PresynapticInput::Electrode(pre.clone()), post.clone(),
+                                               ShortTermPlasticity::new(50.0, 10.0, 0.5), 20.0);
+
+    syn.set_modulation(modulation);
+
+    // The parameter follows the modulation level and is not modulated again from its modulated value
+    pulse(&pre, || {
+        let level = syn.process(0.1);
+        assert_eq!(post.borrow().mod_var(NeuriteModVar::A), modulation.apply(base, level));
+        post.borrow_mut().process(0.1);
+    });
+
+    assert!(post.borrow().mod_var(NeuriteModVar::A) > base);
+
+    syn.unmodulate();
+    assert_eq!(post.borrow().mod_var(NeuriteModVar::A), base);
+}
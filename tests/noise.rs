@@ -0,0 +1,55 @@
+use rand::SeedableRng;
+use neuron_modeler::neural::{Electrode, OrnsteinUhlenbeckElectrode, ShotNoiseElectrode, WhiteNoiseElectrode};
+use neuron_modeler::system::SimRng;
+
+/// Returns the mean and variance of the output of the specified seeded electrode on every 0.1 ms time step over the
+/// specified duration (s).
+fn statistics(electrode: &mut impl Electrode, seconds: usize) -> (f64, f64) {
+    electrode.seed(SimRng::seed_from_u64(5));
+    electrode.on();
+
+    let y: Vec<f64> = (0..seconds * 10000).map(|_| electrode.process(0.1)).collect();
+    let mean = y.iter().sum::<f64>() / y.len() as f64;
+    let var = y.iter().map(|y| (y - mean).powi(2)).sum::<f64>() / y.len() as f64;
+
+    (mean, var)
+}
+
+#[test]
+fn white_noise_variance_scales_with_the_time_step() {
+    let (mean, var) = statistics(&mut WhiteNoiseElectrode::new(3.0, 2.0), 10);
+
+    // The standard deviation of each sample is 2 / sqrt(0.1)
+    assert!((mean - 3.0).abs() < 0.1, "{}", mean);
+    assert!((var / 40.0 - 1.0).abs() < 0.03, "{}", var);
+}
+
+#[test]
+fn ornstein_uhlenbeck_has_the_stationary_mean_and_variance() {
+    let (mean, var) = statistics(&mut OrnsteinUhlenbeckElectrode::new(-1.0, 2.0, 5.0), 200);
+
+    assert!((mean + 1.0).abs() < 0.1, "{}", mean);
+    assert!((var / 4.0 - 1.0).abs() < 0.05, "{}", var);
+}
+
+#[test]
+fn ornstein_uhlenbeck_starts_at_the_mean_when_turned_on() {
+    let mut electrode = OrnsteinUhlenbeckElectrode::new(4.0, 0.5, 5.0);
+    electrode.seed(SimRng::seed_from_u64(5));
+    electrode.on();
+
+    // One step of 0.1 ms moves the output by about 0.5 * sqrt(1 - exp(-0.04)) ~ 0.1 from the mean
+    assert!((electrode.process(0.1) - 4.0).abs() < 0.6);
+
+    electrode.off();
+    assert_eq!(electrode.process(0.1), 0.0);
+}
+
+#[test]
+fn shot_noise_follows_campbells_theorem() {
+    let (mean, var) = statistics(&mut ShotNoiseElectrode::new(2.0, 100.0, 10.0), 200);
+
+    // Rate 0.1 / ms, so the mean is 2 * 0.1 * 10 and the variance 2^2 * 0.1 * 10 / 2
+    assert!((mean / 2.0 - 1.0).abs() < 0.05, "{}", mean);
+    assert!((var / 2.0 - 1.0).abs() < 0.05, "{}", var);
+}
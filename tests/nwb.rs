@@ -0,0 +1,90 @@
+#![cfg(feature = "hdf5")]
+
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+use hdf5::types::VarLenUnicode;
+use neuron_modeler::neural::{CommandElectrode, Electrode, NeuriteVar, Neuron, SpikeModel};
+use neuron_modeler::nwb::NwbSession;
+use neuron_modeler::recording::{BufferMode, ElectrodeVar, ProbeTarget, ProbeVar};
+use neuron_modeler::simulation::Simulation;
+
+/// Returns a path in the temporary directory for the specified file name, unique to this test process.
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("neuron-modeler-{}-{}", std::process::id(), name)).to_string_lossy().into_owned()
+}
+
+/// Returns the specified string attribute of the specified location.
+fn str_attr(loc: &hdf5::Location, name: &str) -> String {
+    loc.attr(name).unwrap().read_scalar::<VarLenUnicode>().unwrap().to_string()
+}
+
+#[test]
+fn session_round_trips_through_an_nwb_file() {
+    let mut sim = Simulation::new(0.1, 1);
+    let neuron = Neuron::new(SpikeModel::TonicSpiking);
+    let electrode = Rc::new(RefCell::new(CommandElectrode::new(14.0)));
+    let id = neuron.soma().borrow().id();
+
+    electrode.borrow_mut().on();
+    neuron.soma().borrow_mut().attach_electrode(electrode.clone());
+    sim.add_electrode(electrode);
+    sim.add_neuron(neuron);
+
+    let v = sim.add_probe(ProbeTarget::Neurite(id), vec![ProbeVar::Neurite(NeuriteVar::V)], 0.1,
+                          BufferMode::Unbounded).unwrap();
+    let i = sim.add_probe(ProbeTarget::Electrode(0), vec![ProbeVar::Electrode(ElectrodeVar::Output)], 0.1,
+                          BufferMode::Unbounded).unwrap();
+    let recorder = sim.add_spike_recorder(&[id]).unwrap();
+
+    sim.run_for(100.0);
+
+    let mut session = NwbSession::new("Driven neuron", "2024-01-01T00:00:00Z");
+    session.add_voltage(sim.probe(v).unwrap(), 0.1).unwrap();
+    session.add_stimulus(sim.probe(i).unwrap(), id, 0.1).unwrap();
+    session.add_units(sim.spike_recorder(recorder).unwrap());
+    assert!(session.add_voltage(sim.probe(i).unwrap(), 0.1).is_err());
+
+    let path = temp_path("session.nwb");
+    session.write(&path).unwrap();
+
+    let file = hdf5::File::open(&path).unwrap();
+    let uuid = id.uuid();
+
+    assert_eq!(str_attr(&file, "neurodata_type"), "NWBFile");
+    assert_eq!(str_attr(&file, "nwb_version"), "2.5.0");
+    assert_eq!(file.dataset("session_start_time").unwrap().read_scalar::<VarLenUnicode>().unwrap().as_str(),
+               "2024-01-01T00:00:00Z");
+
+    // The file creation date is the current UTC time, not the session start time
+    let created = file.dataset("file_create_date").unwrap().read_raw::<VarLenUnicode>().unwrap();
+    assert_eq!(created.len(), 1);
+    assert!(created[0].as_str() > "2024-01-02" && created[0].as_str().ends_with('Z'), "{}", created[0]);
+
+    // Voltages are stored in mV with a conversion to volts, sampled at the time step
+    let voltage = file.group(&format!("acquisition/voltage_{}", uuid)).unwrap();
+    let data = voltage.dataset("data").unwrap();
+    let start = voltage.dataset("starting_time").unwrap();
+
+    assert_eq!(str_attr(&voltage, "neurodata_type"), "CurrentClampSeries");
+    assert_eq!(data.read_raw::<f64>().unwrap(), sim.probe(v).unwrap().trace(ProbeVar::Neurite(NeuriteVar::V)).unwrap());
+    assert_eq!(data.attr("conversion").unwrap().read_scalar::<f64>().unwrap(), 1e-3);
+    assert!((start.attr("rate").unwrap().read_scalar::<f64>().unwrap() - 10000.0).abs() < 1.0e-6);
+
+    let stimulus = file.group(&format!("stimulus/presentation/stimulus_{}", uuid)).unwrap();
+    assert_eq!(str_attr(&stimulus, "neurodata_type"), "CurrentClampStimulusSeries");
+    assert!(stimulus.dataset("data").unwrap().read_raw::<f64>().unwrap().iter().all(|&i| i == 14.0));
+    assert!(file.group(&format!("general/intracellular_ephys/electrode_{}", uuid)).is_ok());
+
+    // Spike times are stored in seconds, indexed per unit
+    let spikes = sim.spike_recorder(recorder).unwrap().spike_times(id);
+    let units = file.group("units").unwrap();
+    let times = units.dataset("spike_times").unwrap().read_raw::<f64>().unwrap();
+
+    assert!(!spikes.is_empty());
+    assert_eq!(times, spikes.iter().map(|t| t / 1000.0).collect::<Vec<f64>>());
+    assert_eq!(units.dataset("spike_times_index").unwrap().read_raw::<u64>().unwrap(), vec![spikes.len() as u64]);
+    assert_eq!(units.dataset("id").unwrap().read_raw::<i64>().unwrap(), vec![0]);
+
+    fs::remove_file(&path).unwrap();
+}
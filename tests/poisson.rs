@@ -0,0 +1,67 @@
+use rand::SeedableRng;
+use neuron_modeler::neural::{Electrode, PoissonElectrode, RateFunction, Refractory};
+use neuron_modeler::system::SimRng;
+
+/// Returns the pulse onset times (ms) of the specified seeded electrode, with single time step pulses, over the
+/// specified duration (s).
+fn onsets(mut electrode: PoissonElectrode, seconds: usize) -> Vec<f64> {
+    electrode.set_duration(0.1);
+    electrode.seed(SimRng::seed_from_u64(3));
+    electrode.on();
+
+    (0..seconds * 10000).filter(|_| electrode.process(0.1) > 0.0).map(|i| (i + 1) as f64 * 0.1).collect()
+}
+
+/// Returns the mean and coefficient of variation of the inter-spike intervals of the specified onset times.
+fn intervals(onsets: &[f64]) -> (f64, f64) {
+    let isi: Vec<f64> = onsets.windows(2).map(|w| w[1] - w[0]).collect();
+    let mean = isi.iter().sum::<f64>() / isi.len() as f64;
+    let var = isi.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / isi.len() as f64;
+
+    (mean, var.sqrt() / mean)
+}
+
+#[test]
+fn constant_rate_matches_the_frequency() {
+    let onsets = onsets(PoissonElectrode::new(1.0, 20.0), 200);
+    let (mean, cv) = intervals(&onsets);
+
+    // 4000 expected events, with a standard deviation of about 63
+    assert!((onsets.len() as f64 - 4000.0).abs() < 250.0, "{}", onsets.len());
+    assert!((mean - 50.0).abs() < 3.0 && (cv - 1.0).abs() < 0.1, "{} {}", mean, cv);
+}
+
+#[test]
+fn dead_time_keeps_the_rate_and_spaces_the_pulses() {
+    let mut electrode = PoissonElectrode::new(1.0, 20.0);
+    electrode.set_refractory(Refractory::DeadTime(10.0));
+
+    let onsets = onsets(electrode, 200);
+    let (_, cv) = intervals(&onsets);
+
+    assert!((onsets.len() as f64 - 4000.0).abs() < 250.0, "{}", onsets.len());
+    assert!(onsets.windows(2).all(|w| w[1] - w[0] > 10.0 - 1.0e-9));
+    assert!(cv < 0.9, "{}", cv);
+}
+
+#[test]
+fn gamma_intervals_keep_the_rate_and_are_more_regular() {
+    let mut electrode = PoissonElectrode::new(1.0, 20.0);
+    electrode.set_refractory(Refractory::Gamma(4));
+
+    let onsets = onsets(electrode, 200);
+    let (_, cv) = intervals(&onsets);
+
+    // The coefficient of variation of order k gamma intervals is 1 / sqrt(k)
+    assert!((onsets.len() as f64 - 4000.0).abs() < 250.0, "{}", onsets.len());
+    assert!((cv - 0.5).abs() < 0.05, "{}", cv);
+}
+
+#[test]
+fn piecewise_rate_follows_its_steps() {
+    let rate = RateFunction::Piecewise(vec![(10000.0, 50.0), (20000.0, 0.0)]);
+    let onsets = onsets(PoissonElectrode::inhomogeneous(1.0, rate), 30);
+
+    assert!(onsets.iter().all(|&t| (10000.0..20000.0 + 0.1).contains(&t)));
+    assert!((onsets.len() as f64 - 500.0).abs() < 100.0, "{}", onsets.len());
+}
@@ -0,0 +1,70 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use neuron_modeler::neural::{CommandElectrode, Electrode};
+use neuron_modeler::protocol::{Protocol, ProtocolRunner, ProtocolStep, StimulusShape};
+
+#[test]
+fn fi_steps_compile_to_increasing_commands() {
+    // 3 sweeps of 10 ms, with a 5 ms step from 2 ms, starting at 1.0 and increasing by 0.5 each sweep
+    let protocol = Protocol::fi_steps(3, 10.0, 2.0, 5.0, 1.0, 0.5);
+
+    assert_eq!(protocol.duration(), 30.0);
+    assert_eq!(protocol.compile(0.1), vec![(20, Some(1.0)), (70, None), (120, Some(1.5)), (170, None),
+                                           (220, Some(2.0)), (270, None)]);
+}
+
+#[test]
+fn pulse_train_edges_follow_width_interval_and_count() {
+    // 3 pulses of 2 ms every 5 ms from 10 ms
+    let step = ProtocolStep::new(10.0, StimulusShape::PulseTrain(4.0, 2.0, 5.0, 3));
+
+    assert_eq!(step.amplitude(0, 9.9), None);
+    assert_eq!(step.amplitude(0, 10.0), Some(4.0));
+    assert_eq!(step.amplitude(0, 11.9), Some(4.0));
+    assert_eq!(step.amplitude(0, 12.0), None);
+    assert_eq!(step.amplitude(0, 15.0), Some(4.0));
+    assert_eq!(step.amplitude(0, 21.9), Some(4.0));
+    assert_eq!(step.amplitude(0, 25.0), None);
+}
+
+#[test]
+fn overlapping_steps_add_and_increments_shift_steps() {
+    let mut ramp = ProtocolStep::new(0.0, StimulusShape::Ramp(0.0, 10.0, 10.0));
+    let mut hold = ProtocolStep::new(5.0, StimulusShape::Hold(1.0));
+    ramp.set_increments(10.0, 0.0);
+    hold.set_increments(0.0, 2.0);
+
+    let mut protocol = Protocol::new(2, 20.0);
+    protocol.add_step(ramp);
+    protocol.add_step(hold);
+
+    assert_eq!(protocol.command(0, 2.5), Some(2.5));
+    assert_eq!(protocol.command(0, 7.5), Some(8.5));
+    assert_eq!(protocol.command(1, 5.0), Some(10.0));
+    assert_eq!(protocol.command(1, 15.0), Some(1.0));
+    assert_eq!(protocol.command(1, 6.0), Some(12.0));
+}
+
+#[test]
+fn runner_drives_the_electrode_and_turns_it_off_at_the_end() {
+    let electrode = Rc::new(RefCell::new(CommandElectrode::new(0.0)));
+    let protocol = Protocol::fi_steps(2, 10.0, 2.0, 5.0, 1.0, 0.5);
+    let mut runner = ProtocolRunner::new(&protocol, electrode.clone(), 0.1);
+
+    let output: Vec<f64> = (0..200).map(|_| {
+        runner.process();
+        electrode.borrow_mut().process(0.1)
+    }).collect();
+
+    assert!(output[..20].iter().all(|&y| y == 0.0));
+    assert!(output[20..70].iter().all(|&y| y == 1.0));
+    assert!(output[70..120].iter().all(|&y| y == 0.0));
+    assert!(output[120..170].iter().all(|&y| y == 1.5));
+    assert!(output[170..].iter().all(|&y| y == 0.0));
+    assert!(runner.finished());
+
+    // The runner does not turn the electrode off again after the protocol
+    electrode.borrow_mut().on();
+    runner.process();
+    assert_eq!(electrode.borrow_mut().process(0.1), 1.5);
+}
@@ -0,0 +1,108 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use neuron_modeler::neural::{CommandElectrode, Electrode, Neurite, NeuriteType, NeuriteVar, SpikeModel, SynapseVar,
+                             SynapticID};
+use neuron_modeler::recording::{BufferMode, ElectrodeVar, Probe, ProbeSource, ProbeTarget, ProbeVar};
+use neuron_modeler::simulation::Simulation;
+
+/// Returns a simulation with a tonic spiking soma driven by a command electrode (electrode 0), and the soma.
+fn driven() -> (Simulation, Rc<RefCell<Neurite>>) {
+    let mut sim = Simulation::new(0.1, 1);
+    let neurite = Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, SpikeModel::TonicSpiking)));
+    let electrode = Rc::new(RefCell::new(CommandElectrode::new(14.0)));
+
+    electrode.borrow_mut().on();
+    neurite.borrow_mut().attach_electrode(electrode.clone());
+    sim.add_electrode(electrode);
+    sim.add_neurite(neurite.clone());
+
+    (sim, neurite)
+}
+
+#[test]
+fn probe_samples_at_its_interval_after_the_step() {
+    let (mut sim, neurite) = driven();
+    let id = neurite.borrow().id();
+    let every = sim.add_probe(ProbeTarget::Neurite(id), vec![ProbeVar::Neurite(NeuriteVar::V)], 0.1,
+                              BufferMode::Unbounded).unwrap();
+    let sparse = sim.add_probe(ProbeTarget::Electrode(0), vec![ProbeVar::Electrode(ElectrodeVar::Output)], 1.0,
+                               BufferMode::Unbounded).unwrap();
+
+    sim.run_for(10.0);
+
+    let every = sim.probe(every).unwrap();
+    let sparse = sim.probe(sparse).unwrap();
+    let v = every.trace(ProbeVar::Neurite(NeuriteVar::V)).unwrap();
+
+    // Samples are taken at the time at the end of each step
+    assert_eq!(every.times().len(), 100);
+    assert!((every.times()[0] - 0.1).abs() < 1.0e-12 && (every.times()[99] - 10.0).abs() < 1.0e-9);
+    assert_eq!(*v.last().unwrap(), neurite.borrow().v());
+
+    // Sparse samples are due on multiples of the interval, the first one at the end of the first step
+    let times: Vec<f64> = sparse.times().iter().map(|t| (t * 10.0).round() / 10.0).collect();
+    assert_eq!(times, vec![0.1, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0]);
+    assert!(sparse.samples().iter().all(|s| s == &vec![14.0]));
+}
+
+#[test]
+fn bounded_probes_stop_and_ring_probes_keep_the_last_samples() {
+    let (mut sim, neurite) = driven();
+    let id = neurite.borrow().id();
+    let vars = vec![ProbeVar::Neurite(NeuriteVar::V), ProbeVar::Neurite(NeuriteVar::U)];
+    let bounded = sim.add_probe(ProbeTarget::Neurite(id), vars.clone(), 0.1, BufferMode::Bounded(3)).unwrap();
+    let ring = sim.add_probe(ProbeTarget::Neurite(id), vars, 0.1, BufferMode::Ring(3)).unwrap();
+
+    sim.run_for(1.0);
+
+    let bounded = sim.probe(bounded).unwrap();
+    let ring = sim.probe(ring).unwrap();
+    let times = |p: &Probe| p.times().iter().map(|t| (t * 10.0).round() as u32).collect::<Vec<u32>>();
+
+    assert!(bounded.full() && !ring.full());
+    assert_eq!(times(bounded), vec![1, 2, 3]);
+    assert_eq!(times(ring), vec![8, 9, 10]);
+    assert_eq!(ring.samples().back().unwrap(), &vec![neurite.borrow().v(), neurite.borrow().probe(NeuriteVar::U)]);
+}
+
+#[test]
+fn probes_reject_unknown_targets_and_foreign_variables() {
+    let (mut sim, neurite) = driven();
+    let id = neurite.borrow().id();
+    let other = Neurite::new(NeuriteType::Soma, SpikeModel::TonicSpiking).id();
+    let v = vec![ProbeVar::Neurite(NeuriteVar::V)];
+
+    assert!(sim.add_probe(ProbeTarget::Neurite(other), v.clone(), 0.1, BufferMode::Unbounded).is_err());
+    assert!(sim.add_probe(ProbeTarget::Electrode(1), v.clone(), 0.1, BufferMode::Unbounded).is_err());
+    assert!(sim.add_probe(ProbeTarget::Synapse(SynapticID::new()), v, 0.1, BufferMode::Unbounded).is_err());
+    assert!(Probe::new(ProbeSource::Neurite(neurite), vec![ProbeVar::Synapse(SynapseVar::W)], 0.1,
+                       BufferMode::Unbounded).is_err());
+    assert!(sim.add_probe(ProbeTarget::Neurite(id), vec![ProbeVar::Electrode(ElectrodeVar::Voltage)], 0.1,
+                          BufferMode::Unbounded).is_err());
+}
+
+#[test]
+fn spike_recorder_records_only_its_neurites() {
+    let (mut sim, neurite) = driven();
+    let quiet = Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, SpikeModel::TonicSpiking)));
+    let id = neurite.borrow().id();
+    let quiet_id = quiet.borrow().id();
+
+    sim.add_neurite(quiet);
+
+    let recorder = sim.add_spike_recorder(&[id, quiet_id]).unwrap();
+    let y = sim.add_probe(ProbeTarget::Neurite(id), vec![ProbeVar::Neurite(NeuriteVar::Y)], 0.1,
+                          BufferMode::Unbounded).unwrap();
+
+    sim.run_for(100.0);
+
+    let recorder = sim.spike_recorder(recorder).unwrap();
+    let spikes = recorder.spike_times(id);
+    let steps = sim.probe(y).unwrap().trace(ProbeVar::Neurite(NeuriteVar::Y)).unwrap();
+
+    assert!(!spikes.is_empty() && spikes.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(spikes.len(), steps.iter().filter(|&&y| y > 0.0).count());
+    assert!(recorder.spike_times(quiet_id).is_empty());
+    assert_eq!(recorder.events().len(), spikes.len());
+    assert!(sim.add_spike_recorder(&[Neurite::new(NeuriteType::Soma, SpikeModel::TonicSpiking).id()]).is_err());
+}
@@ -0,0 +1,56 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use neuron_modeler::neural::{DopamineSignal, RewardModulatedPlasticity, SpikeTimingDependentPlasticity};
+
+/// Returns the synaptic weights of reward-modulated STDP on each time step (1 ms), for a presynaptic spike at 0 ms, a
+/// postsynaptic spike at 10 ms and a reward at the specified time step.
+fn weights(reward: usize, steps: usize) -> (Vec<f64>, RewardModulatedPlasticity) {
+    let dopamine = Rc::new(RefCell::new(DopamineSignal::new(200.0, 0.0)));
+    let stdp = SpikeTimingDependentPlasticity::pair(0.01, 0.012, 20.0, 20.0, 0.0, 1.0);
+    let mut rstdp = RewardModulatedPlasticity::new(stdp, 1000.0, dopamine.clone());
+    let mut w = 0.5;
+
+    let weights = (0..steps).map(|t| {
+        if t == reward {
+            dopamine.borrow_mut().reward(0.5);
+        }
+
+        dopamine.borrow_mut().process(1.0);
+        w = rstdp.learn(t == 0, t == 10, w, 1.0);
+        w
+    }).collect();
+
+    (weights, rstdp)
+}
+
+#[test]
+fn dopamine_decays_to_the_tonic_level() {
+    let mut dopamine = DopamineSignal::new(50.0, 0.1);
+
+    dopamine.reward(1.0);
+    assert_eq!(dopamine.level(), 1.1);
+
+    for _ in 0..500 {
+        dopamine.process(0.1);
+    }
+
+    assert!((dopamine.level() - (0.1 + (-1.0f64).exp())).abs() < 1.0e-12, "{}", dopamine.level());
+}
+
+#[test]
+fn weights_change_only_when_dopamine_arrives() {
+    let (w, rstdp) = weights(100, 200);
+
+    // The spike pairing leaves an eligibility trace, but no weight change before the reward
+    assert!(w[..100].iter().all(|&w| w == 0.5));
+    assert!(rstdp.c() > 0.0);
+    assert!(w[150] > 0.5 && w[199] > w[150]);
+}
+
+#[test]
+fn later_rewards_reinforce_less() {
+    let (prompt, _) = weights(20, 3000);
+    let (late, _) = weights(1000, 3000);
+
+    assert!(late[2999] > 0.5 && prompt[2999] > late[2999], "{} {}", prompt[2999], late[2999]);
+}
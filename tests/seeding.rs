@@ -0,0 +1,43 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use neuron_modeler::neural::{Electrode, PoissonElectrode};
+use neuron_modeler::simulation::Simulation;
+
+/// Returns the output bits of the specified number of Poisson electrodes (5 Hz) of a simulation with the specified
+/// seed on every time step of 3 s.
+fn traces(seed: u64, electrodes: usize) -> Vec<Vec<u64>> {
+    let mut sim = Simulation::new(0.1, seed);
+    let electrodes: Vec<Rc<RefCell<PoissonElectrode>>> = (0..electrodes)
+        .map(|_| Rc::new(RefCell::new(PoissonElectrode::new(1.0, 5.0))))
+        .collect();
+
+    for e in &electrodes {
+        e.borrow_mut().on();
+        sim.add_electrode(e.clone());
+    }
+
+    (0..30000).map(|_| {
+        sim.step();
+        electrodes.iter().map(|e| e.borrow().output().to_bits()).collect()
+    }).collect()
+}
+
+#[test]
+fn equal_seeds_give_identical_traces() {
+    assert_eq!(traces(7, 2), traces(7, 2));
+}
+
+#[test]
+fn different_seeds_give_different_traces() {
+    assert_ne!(traces(7, 2), traces(8, 2));
+}
+
+#[test]
+fn electrodes_have_independent_sub_streams() {
+    let two = traces(7, 2);
+    let three = traces(7, 3);
+
+    // Adding an electrode leaves the earlier electrodes unchanged, and each electrode has its own stream
+    assert!(two.iter().zip(&three).all(|(a, b)| a[..] == b[..2]));
+    assert!(two.iter().any(|t| t[0] != t[1]));
+}
@@ -0,0 +1,75 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use neuron_modeler::neural::{RateSensor, ReceptiveFieldSensor, Sensor, ThresholdSensor, TimeSeriesSensor};
+use neuron_modeler::simulation::Simulation;
+
+#[test]
+fn time_series_interpolates_and_ends_at_zero() {
+    let mut sensor = TimeSeriesSensor::new(vec![0.0, 2.0, 4.0], 1.0).unwrap();
+    let y: Vec<f64> = (0..8).map(|_| sensor.process(0.5)).collect();
+
+    assert_eq!(y, vec![0.0, 1.0, 2.0, 3.0, 4.0, 4.0, 0.0, 0.0]);
+    assert!(TimeSeriesSensor::new(vec![1.0], 0.0).is_err());
+    assert!(TimeSeriesSensor::new(vec![1.0], f64::NAN).is_err());
+}
+
+#[test]
+fn looping_time_series_wraps_to_the_first_sample() {
+    let mut sensor = TimeSeriesSensor::new(vec![0.0, 2.0], 1.0).unwrap();
+    sensor.set_looping(true);
+
+    let y: Vec<f64> = (0..6).map(|_| sensor.process(0.5)).collect();
+    assert_eq!(y, vec![0.0, 1.0, 2.0, 1.0, 0.0, 1.0]);
+
+    // Streamed samples extend the loop, which now restarts at 3 ms
+    sensor.push_samples(&[4.0]);
+    let y: Vec<f64> = (0..4).map(|_| sensor.process(0.5)).collect();
+    assert_eq!(y, vec![0.0, 1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn receptive_field_prefers_a_center_spot_over_uniform_light() {
+    let mut sensor = ReceptiveFieldSensor::new(9, 9, 4.0, 4.0, 1.0, 3.0, 1.0);
+    let mut spot = vec![0.0; 81];
+    spot[40] = 1.0;
+
+    sensor.set_image(spot).unwrap();
+    let center = sensor.process(0.1);
+    sensor.set_image(vec![1.0; 81]).unwrap();
+    let uniform = sensor.process(0.1);
+
+    assert!(center > 0.0 && center > uniform, "{} {}", center, uniform);
+    assert!(sensor.set_image(vec![1.0; 80]).is_err());
+}
+
+#[test]
+fn threshold_and_rate_sensors_follow_their_variable() {
+    let var = Rc::new(Cell::new(0.5));
+    let mut threshold = ThresholdSensor::new(var.clone(), 1.0);
+    let mut rate = RateSensor::new(var.clone(), 20.0, 50.0);
+
+    assert_eq!((threshold.process(0.1), rate.process(0.1)), (0.0, 10.0));
+
+    var.set(1.0);
+    assert_eq!((threshold.process(0.1), rate.process(0.1)), (1.0, 20.0));
+
+    var.set(5.0);
+    assert_eq!(rate.process(0.1), 50.0);
+
+    var.set(-1.0);
+    assert_eq!((threshold.process(0.1), rate.process(0.1)), (0.0, 0.0));
+}
+
+#[test]
+fn simulation_processes_sensors_every_step() {
+    let mut sim = Simulation::new(0.1, 1);
+    let sensor = Rc::new(RefCell::new(TimeSeriesSensor::new(vec![0.0, 1.0], 1.0).unwrap()));
+
+    sim.add_sensor(sensor.clone());
+
+    for _ in 0..5 {
+        sim.step();
+    }
+
+    assert!((sensor.borrow().output() - 0.4).abs() < 1.0e-12, "{}", sensor.borrow().output());
+}
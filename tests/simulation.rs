@@ -0,0 +1,59 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use neuron_modeler::neural::{Electrode, Neurite, NeuriteType, Neuron, PulseElectrode, SpikeModel};
+use neuron_modeler::simulation::Simulation;
+
+/// Returns a regular spiking neuron with a dendrite.
+fn neuron() -> Neuron {
+    let neuron = Neuron::new(SpikeModel::RegularSpiking);
+    let dendrite = Neurite::new(NeuriteType::BasalProximal, SpikeModel::RegularSpikingPyramidalL2L3Dendrite);
+
+    neuron.soma().borrow_mut().add_child(Rc::new(RefCell::new(dendrite))).unwrap();
+    neuron
+}
+
+#[test]
+fn run_for_processes_neurons_and_neurites_once_per_step() {
+    for time_inc in [0.1, 0.05] {
+        let mut sim = Simulation::new(time_inc, 1);
+        let neurite = Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, SpikeModel::TonicSpiking)));
+
+        sim.add_neuron(neuron());
+        sim.add_neurite(neurite.clone());
+        sim.run_for(10.0);
+
+        // The same neuron and neurite processed by hand over the simulation time step
+        let mut control = neuron();
+        let mut control_neurite = Neurite::new(NeuriteType::Soma, SpikeModel::TonicSpiking);
+
+        for _ in 0..(10.0 / time_inc).round() as usize {
+            control.process(time_inc);
+            control_neurite.process(time_inc);
+        }
+
+        let v = |n: &Neuron| n.neurites().iter().map(|n| n.borrow().v()).collect::<Vec<f64>>();
+
+        assert!((sim.time() - 10.0).abs() < 1.0e-9, "{}", sim.time());
+        assert_eq!(v(&sim.neurons()[0]), v(&control));
+        assert_eq!(neurite.borrow().v(), control_neurite.v());
+    }
+}
+
+#[test]
+fn electrodes_are_processed_every_step() {
+    let mut sim = Simulation::new(0.1, 1);
+    let electrode = Rc::new(RefCell::new(PulseElectrode::new(30.0, 1.0)));
+
+    electrode.borrow_mut().on();
+    sim.add_electrode(electrode.clone());
+
+    // The pulse starts on the first step and ends after its duration
+    sim.step();
+    assert_eq!(electrode.borrow().output(), 30.0);
+
+    sim.run_for(0.5);
+    assert_eq!(electrode.borrow().output(), 30.0);
+
+    sim.run_for(1.0);
+    assert_eq!(electrode.borrow().output(), 0.0);
+}
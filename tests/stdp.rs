@@ -0,0 +1,54 @@
+use neuron_modeler::neural::SpikeTimingDependentPlasticity;
+
+/// Returns the weight change of the specified STDP data on each time step (1 ms), for presynaptic and postsynaptic
+/// spikes at the specified time steps.
+fn weight_changes(stdp: &mut SpikeTimingDependentPlasticity, pre: &[usize], post: &[usize], steps: usize) -> Vec<f64> {
+    (0..steps).map(|t| stdp.dw(pre.contains(&t), post.contains(&t), 1.0)).collect()
+}
+
+#[test]
+fn pair_rule_potentiates_pre_before_post() {
+    let mut stdp = SpikeTimingDependentPlasticity::pair(0.01, 0.012, 20.0, 20.0, 0.0, 1.0);
+    let dw = weight_changes(&mut stdp, &[0], &[10], 20);
+
+    assert_eq!(dw[0], 0.0);
+    assert!((dw[10] - 0.01 * (-10.0f64 / 20.0).exp()).abs() < 1.0e-12);
+    assert_eq!(dw.iter().filter(|&&dw| dw != 0.0).count(), 1);
+}
+
+#[test]
+fn pair_rule_depresses_post_before_pre() {
+    let mut stdp = SpikeTimingDependentPlasticity::pair(0.01, 0.012, 20.0, 30.0, 0.0, 1.0);
+    let dw = weight_changes(&mut stdp, &[15], &[5], 20);
+
+    assert_eq!(dw[5], 0.0);
+    assert!((dw[15] + 0.012 * (-10.0f64 / 30.0).exp()).abs() < 1.0e-12);
+}
+
+#[test]
+fn triplet_rule_adds_the_slow_postsynaptic_trace() {
+    let mut stdp = SpikeTimingDependentPlasticity::triplet(0.01, 0.0, 0.02, 0.0, 20.0, 20.0, 100.0, 50.0, 0.0, 1.0);
+    let dw = weight_changes(&mut stdp, &[0], &[10, 20], 30);
+
+    // The first postsynaptic spike has no earlier postsynaptic spike, the second one is 10 ms after the first
+    assert!((dw[10] - 0.01 * (-10.0f64 / 20.0).exp()).abs() < 1.0e-12);
+    assert!((dw[20] - (-20.0f64 / 20.0).exp() * (0.01 + 0.02 * (-10.0f64 / 50.0).exp())).abs() < 1.0e-12);
+}
+
+#[test]
+fn learning_keeps_the_weight_within_bounds() {
+    let mut potentiation = SpikeTimingDependentPlasticity::pair(0.5, 0.0, 20.0, 20.0, 0.2, 0.8);
+    let mut depression = SpikeTimingDependentPlasticity::pair(0.0, 0.5, 20.0, 20.0, 0.2, 0.8);
+    let mut up = 0.5;
+    let mut down = 0.5;
+
+    for t in 0..100 {
+        up = potentiation.learn(t % 2 == 0, t % 2 == 1, up, 1.0);
+        down = depression.learn(t % 2 == 0, t % 2 == 1, down, 1.0);
+
+        assert!((0.2..=0.8).contains(&up) && (0.2..=0.8).contains(&down));
+    }
+
+    assert_eq!(up, 0.8);
+    assert_eq!(down, 0.2);
+}
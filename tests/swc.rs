@@ -0,0 +1,118 @@
+use neuron_modeler::neural::NeuriteType;
+use neuron_modeler::swc::{parse, SwcImport, SwcReport, SwcSplit};
+
+/// A soma of two points with a branching basal dendrite, an axon and an apical dendrite.
+const CELL: &str = "\
+# id type x y z radius parent
+1 1 0 0 0 5 -1
+2 1 0 5 0 5 1
+3 3 10 0 0 1 1
+4 3 20 0 0 1 3
+5 3 30 5 0 0.5 4
+6 3 40 5 0 0.5 5
+7 3 30 -5 0 0.5 4
+8 2 -10 0 0 0.5 1
+9 2 -20 0 0 0.5 8
+10 4 0 15 0 2 2
+11 4 0 25 0 2 10
+";
+
+/// Returns the neurite type, point IDs, length and radius of every placed neurite of the specified report.
+fn placed(report: &SwcReport) -> Vec<(NeuriteType, Vec<i64>, f64, f64)> {
+    report.neurites.iter().map(|n| (n.neurite_type, n.points.clone(), n.length, n.radius)).collect()
+}
+
+/// Returns true if the specified values are equal within rounding.
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1.0e-9
+}
+
+#[test]
+fn sections_are_lumped_into_neurites() {
+    let (neuron, report) = SwcImport::new().import(&parse(CELL).unwrap()).unwrap();
+    let neurites = placed(&report);
+    let branch = 125.0f64.sqrt();
+
+    assert_eq!(report.points, 11);
+    assert_eq!(report.placed, 6);
+    assert!(report.unplaced.is_empty());
+    assert_eq!(neuron.neurites().len(), 6);
+
+    let expected = [
+        (NeuriteType::Soma, vec![1, 2], 0.0, 5.0),
+        (NeuriteType::BasalProximal, vec![3, 4], 20.0, 1.0),
+        (NeuriteType::BasalDistal, vec![5, 6], branch + 10.0, 0.5),
+        (NeuriteType::BasalDistal, vec![7], branch, 0.5),
+        (NeuriteType::Axon, vec![8, 9], 20.0, 0.5),
+        (NeuriteType::ApicalTrunk, vec![10, 11], 20.0, 2.0)
+    ];
+
+    assert_eq!(neurites.len(), expected.len());
+
+    for (n, e) in neurites.iter().zip(&expected) {
+        assert_eq!((n.0, &n.1), (e.0, &e.1));
+        assert!(close(n.2, e.2) && close(n.3, e.3), "{:?}", n);
+    }
+
+    // The report refers to the neurites of the neuron
+    let ids: Vec<_> = neuron.neurites().iter().map(|n| n.borrow().id()).collect();
+    assert!(report.neurites.iter().all(|n| ids.contains(&n.neurite)));
+}
+
+#[test]
+fn stems_leave_the_soma_proximal() {
+    for split in [SwcSplit::BranchOrder(0), SwcSplit::Distance(5.0)] {
+        let mut import = SwcImport::new();
+        import.set_basal_split(split);
+
+        let (_, report) = import.import(&parse(CELL).unwrap()).unwrap();
+        let basal: Vec<(NeuriteType, Vec<i64>)> = report.neurites.iter()
+            .filter(|n| matches!(n.neurite_type, NeuriteType::BasalProximal | NeuriteType::BasalDistal))
+            .map(|n| (n.neurite_type, n.points.clone()))
+            .collect();
+
+        assert!(report.unplaced.is_empty(), "{:?}", split);
+        assert_eq!(basal, [(NeuriteType::BasalProximal, vec![3]), (NeuriteType::BasalDistal, vec![4]),
+                           (NeuriteType::BasalDistal, vec![5, 6]), (NeuriteType::BasalDistal, vec![7])],
+                   "{:?}", split);
+    }
+}
+
+#[test]
+fn long_sections_are_split() {
+    let mut import = SwcImport::new();
+    import.set_length(10.0);
+
+    let (_, report) = import.import(&parse(CELL).unwrap()).unwrap();
+    let axon: Vec<(Vec<i64>, f64)> = report.neurites.iter()
+        .filter(|n| n.neurite_type == NeuriteType::Axon)
+        .map(|n| (n.points.clone(), n.length))
+        .collect();
+
+    assert_eq!(axon, [(vec![8], 10.0), (vec![9], 10.0)]);
+    assert_eq!(report.placed, 10);
+}
+
+#[test]
+fn unconnected_and_untyped_points_are_reported() {
+    let text = format!("{}12 3 0 0 0 1 99\n13 3 0 -10 0 1 12\n14 7 0 0 10 1 1\n", CELL);
+    let (neuron, report) = SwcImport::new().import(&parse(&text).unwrap()).unwrap();
+    let unplaced: Vec<(Vec<i64>, &str)> = report.unplaced.iter().map(|u| (u.points.clone(), u.reason)).collect();
+
+    assert_eq!(report.placed, 6);
+    assert_eq!(neuron.neurites().len(), 6);
+    assert_eq!(unplaced.len(), 3);
+    assert!(unplaced.contains(&(vec![12], "Parent point not found.")), "{:?}", unplaced);
+    assert!(unplaced.contains(&(vec![13], "Parent compartment could not be placed.")), "{:?}", unplaced);
+    assert!(unplaced.contains(&(vec![14], "Structure type has no neurite type.")), "{:?}", unplaced);
+}
+
+#[test]
+fn invalid_reconstructions_are_rejected() {
+    let import = SwcImport::new();
+
+    assert!(parse("1 1 0 0 0 5").is_err());
+    assert!(parse("1 1 0 0 x 5 -1").is_err());
+    assert!(import.import(&parse("1 3 0 0 0 1 -1\n2 3 10 0 0 1 1\n").unwrap()).is_err());
+    assert!(import.import(&parse("1 1 0 0 0 5 -1\n1 3 10 0 0 1 1\n").unwrap()).is_err());
+}
@@ -0,0 +1,99 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use neuron_modeler::neural::{Electrode, ExcitatorySynapse, GapJunctionSynapse, Neurite, NeuriteType, PresynapticInput,
+                             PulseElectrode, ShortTermPlasticity, ShuntingInhibitorySynapse, SpikeModel, Synapse};
+
+/// Returns a new unstimulated soma of the specified spike model.
+fn soma(spike_model: SpikeModel) -> Rc<RefCell<Neurite>> {
+    Rc::new(RefCell::new(Neurite::new(NeuriteType::Soma, spike_model)))
+}
+
+#[test]
+fn gap_junction_current_flows_down_the_voltage_difference() {
+    // An accommodation soma starts at -65 mV and a bistability soma at -61 mV
+    let a = soma(SpikeModel::Accommodation);
+    let b = soma(SpikeModel::Bistability);
+    let mut gap = GapJunctionSynapse::new(PresynapticInput::Neurite(a.clone()), b.clone(), 0.5);
+
+    gap.process(0.1);
+    assert_eq!(gap.input(), 0.5 * (-65.0 - -61.0));
+    assert_eq!(gap.reverse_input(), -gap.input());
+
+    let mut same = GapJunctionSynapse::new(PresynapticInput::Neurite(a), soma(SpikeModel::Accommodation), 0.5);
+    same.process(0.1);
+    assert_eq!((same.input(), same.reverse_input()), (0.0, 0.0));
+}
+
+#[test]
+fn shunting_inhibition_pulls_toward_the_reversal_potential() {
+    // The postsynaptic accommodation soma is not processed, so it stays at -65 mV
+    let post = soma(SpikeModel::Accommodation);
+    let electrode = Rc::new(RefCell::new(PulseElectrode::new(10.0, 5.0)));
+    let mut synapses: Vec<ShuntingInhibitorySynapse> = [-70.0, -65.0, -60.0].iter()
+        .map(|&e_rev| {
+            let stp = ShortTermPlasticity::new(50.0, 10.0, 0.5);
+            ShuntingInhibitorySynapse::new(PresynapticInput::Electrode(electrode.clone()), post.clone(), stp, -1.0,
+                                           e_rev, 5.0, 1.0)
+        })
+        .collect();
+
+    // Let the depression recover before the presynaptic pulse
+    for step in 0..200 {
+        if step == 100 {
+            electrode.borrow_mut().on();
+        }
+
+        electrode.borrow_mut().process(0.1);
+        synapses.iter_mut().for_each(|s| { s.process(0.1); });
+    }
+
+    let inputs: Vec<f64> = synapses.iter().map(|s| s.input()).collect();
+    assert!(inputs[0] < 0.0 && inputs[1] == 0.0 && inputs[2] > 0.0, "{:?}", inputs);
+    assert_eq!(inputs[0], -inputs[2]);
+}
+
+#[test]
+fn gap_junction_current_reaches_only_its_own_neurites() {
+    let a = soma(SpikeModel::Accommodation);
+    let b = soma(SpikeModel::Bistability);
+    let mut gap = GapJunctionSynapse::new(PresynapticInput::Neurite(a.clone()), b.clone(), 0.5);
+
+    gap.process(0.1);
+
+    let other = soma(SpikeModel::Accommodation);
+    assert_eq!(gap.input_to(b.borrow().id()), gap.input());
+    assert_eq!(gap.input_to(a.borrow().id()), gap.reverse_input());
+    assert_eq!(gap.input_to(other.borrow().id()), 0.0);
+}
+
+#[test]
+fn attached_synapse_input_drives_the_membrane_potential() {
+    let electrode = Rc::new(RefCell::new(PulseElectrode::new(10.0, 5.0)));
+    let post = soma(SpikeModel::Accommodation);
+    let control = soma(SpikeModel::Accommodation);
+    let stp = ShortTermPlasticity::new(50.0, 10.0, 0.5);
+    let syn = Rc::new(RefCell::new(ExcitatorySynapse::new(PresynapticInput::Electrode(electrode.clone()), post.clone(),
+                                                          stp, 5.0, 1.0)));
+    let id = syn.borrow().syn_id();
+
+    post.borrow_mut().attach_synapse(syn.clone()).unwrap();
+    assert!(post.borrow_mut().attach_synapse(syn.clone()).is_err());
+
+    for step in 0..120 {
+        if step == 100 {
+            electrode.borrow_mut().on();
+        }
+
+        electrode.borrow_mut().process(0.1);
+        syn.borrow_mut().process(0.1);
+        post.borrow_mut().process(0.1);
+        control.borrow_mut().process(0.1);
+    }
+
+    assert!(syn.borrow().input() > 0.0);
+    assert!(post.borrow().v() > control.borrow().v(), "{} {}", post.borrow().v(), control.borrow().v());
+
+    assert!(post.borrow_mut().detach_synapse(id));
+    assert!(!post.borrow_mut().detach_synapse(id));
+    assert!(post.borrow().synapses().is_empty());
+}